@@ -0,0 +1,244 @@
+//! Typed JSON-RPC batch requests.
+//!
+//! Queue calls with a [`BatchRequest`], keeping the returned
+//! [`BatchHandle`]s, and then use the handles to get typed results from the
+//! [`BatchResponses`].
+
+use std::marker::PhantomData;
+
+use bitcoin::{BlockHash, Txid};
+use jsonrpsee::{
+    core::{client::ClientT, params::BatchRequestBuilder, traits::ToRpcParams, ClientError},
+    rpc_params,
+    types::ErrorObjectOwned,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::client::{
+    BlockchainInfo, GetBlockVerbosity, GetRawTransactionVerbosity, Header, MiningInfo, NetworkInfo,
+    RawMempoolTxInfo,
+};
+
+/// Error for a single entry in a batch response
+#[derive(Debug, thiserror::Error)]
+pub enum BatchEntryError {
+    #[error("error response")]
+    Rpc(#[from] ErrorObjectOwned),
+    #[error("failed to deserialize response")]
+    Deserialize(#[from] serde_json::Error),
+    #[error("no response for batch entry {index}")]
+    Missing { index: usize },
+}
+
+/// Handle used to retrieve the result of a queued call from
+/// [`BatchResponses`]
+#[derive(Debug)]
+pub struct BatchHandle<T> {
+    index: usize,
+    _response: PhantomData<fn() -> T>,
+}
+
+impl<T> BatchHandle<T> {
+    /// Position of the call in the batch
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<T> Clone for BatchHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for BatchHandle<T> {}
+
+/// Builder for a batch of calls.
+/// Typed methods are provided for queueing calls to `getbestblockhash`,
+/// `getblockcount`, `getblockhash`, `getblockheader`, `getblockchaininfo`,
+/// `getmininginfo`, `getmempoolentry`, `getnetworkinfo` and
+/// `sendrawtransaction` from [`crate::MainClient`], `getblock` from
+/// [`crate::client::GetBlockClient`], and `getrawtransaction` from
+/// [`crate::client::GetRawTransactionClient`].
+/// These use the same method names and params as the client methods with
+/// the same names.
+/// Use [`Self::insert`] to queue calls to any other method.
+#[derive(Clone, Debug, Default)]
+pub struct BatchRequest {
+    builder: BatchRequestBuilder<'static>,
+    len: usize,
+}
+
+impl BatchRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of queued calls
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Queue a call to any method, with the expected response type `T`
+    pub fn insert<T, Params>(
+        &mut self,
+        method: &'static str,
+        params: Params,
+    ) -> Result<BatchHandle<T>, serde_json::Error>
+    where
+        T: DeserializeOwned,
+        Params: ToRpcParams,
+    {
+        self.builder.insert(method, params)?;
+        let handle = BatchHandle {
+            index: self.len,
+            _response: PhantomData,
+        };
+        self.len += 1;
+        Ok(handle)
+    }
+
+    pub fn getbestblockhash(&mut self) -> Result<BatchHandle<BlockHash>, serde_json::Error> {
+        self.insert("getbestblockhash", rpc_params![])
+    }
+
+    pub fn getblockcount(&mut self) -> Result<BatchHandle<usize>, serde_json::Error> {
+        self.insert("getblockcount", rpc_params![])
+    }
+
+    pub fn getblockhash(
+        &mut self,
+        height: usize,
+    ) -> Result<BatchHandle<BlockHash>, serde_json::Error> {
+        self.insert("getblockhash", rpc_params![height])
+    }
+
+    pub fn getblockheader(
+        &mut self,
+        block_hash: BlockHash,
+    ) -> Result<BatchHandle<Header>, serde_json::Error> {
+        self.insert("getblockheader", rpc_params![block_hash])
+    }
+
+    pub fn get_blockchain_info(
+        &mut self,
+    ) -> Result<BatchHandle<BlockchainInfo>, serde_json::Error> {
+        self.insert("getblockchaininfo", rpc_params![])
+    }
+
+    pub fn get_mining_info(&mut self) -> Result<BatchHandle<MiningInfo>, serde_json::Error> {
+        self.insert("getmininginfo", rpc_params![])
+    }
+
+    pub fn get_mempool_entry(
+        &mut self,
+        txid: Txid,
+    ) -> Result<BatchHandle<RawMempoolTxInfo>, serde_json::Error> {
+        self.insert("getmempoolentry", rpc_params![txid])
+    }
+
+    pub fn get_network_info(&mut self) -> Result<BatchHandle<NetworkInfo>, serde_json::Error> {
+        self.insert("getnetworkinfo", rpc_params![])
+    }
+
+    // Max fee rate: BTC/kvB value
+    // Max burn amount: BTC value
+    pub fn send_raw_transaction(
+        &mut self,
+        tx_hex: String,
+        max_fee_rate: Option<f64>,
+        max_burn_amount: Option<f64>,
+    ) -> Result<BatchHandle<Txid>, serde_json::Error> {
+        self.insert(
+            "sendrawtransaction",
+            rpc_params![tx_hex, max_fee_rate, max_burn_amount],
+        )
+    }
+
+    pub fn get_block<Verbosity>(
+        &mut self,
+        block_hash: BlockHash,
+        verbosity: Verbosity,
+    ) -> Result<BatchHandle<<Verbosity as GetBlockVerbosity>::Response>, serde_json::Error>
+    where
+        Verbosity: GetBlockVerbosity + Serialize,
+    {
+        self.insert("getblock", rpc_params![block_hash, verbosity])
+    }
+
+    pub fn get_raw_transaction<T>(
+        &mut self,
+        txid: Txid,
+        verbose: T,
+        block_hash: Option<BlockHash>,
+    ) -> Result<BatchHandle<<T as GetRawTransactionVerbosity>::Response>, serde_json::Error>
+    where
+        T: GetRawTransactionVerbosity + Serialize,
+    {
+        self.insert("getrawtransaction", rpc_params![txid, verbose, block_hash])
+    }
+
+    /// Send the batch. Returns an error if the batch is empty, or if the
+    /// request failed.
+    /// Errors for individual calls are returned by [`BatchResponses::get`].
+    pub async fn send<C>(self, client: &C) -> Result<BatchResponses, ClientError>
+    where
+        C: ClientT,
+    {
+        let responses = client
+            .batch_request::<JsonValue>(self.builder)
+            .await?
+            .into_iter()
+            .map(|response| response.map_err(|err| err.into_owned()))
+            .collect();
+        Ok(BatchResponses { responses })
+    }
+}
+
+/// Responses to a [`BatchRequest`], in the order that calls were queued
+#[derive(Clone, Debug)]
+pub struct BatchResponses {
+    responses: Vec<Result<JsonValue, ErrorObjectOwned>>,
+}
+
+impl BatchResponses {
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+
+    /// Get the typed result for a queued call
+    pub fn get<T>(&self, handle: BatchHandle<T>) -> Result<T, BatchEntryError>
+    where
+        T: DeserializeOwned,
+    {
+        match self.responses.get(handle.index) {
+            Some(Ok(value)) => Ok(T::deserialize(value)?),
+            Some(Err(err)) => Err(BatchEntryError::Rpc(err.clone())),
+            None => Err(BatchEntryError::Missing {
+                index: handle.index,
+            }),
+        }
+    }
+
+    /// Get the typed results for several queued calls, in order
+    pub fn get_all<'a, T, I>(
+        &'a self,
+        handles: I,
+    ) -> impl Iterator<Item = Result<T, BatchEntryError>> + 'a
+    where
+        T: DeserializeOwned,
+        I: IntoIterator<Item = BatchHandle<T>>,
+        I::IntoIter: 'a,
+    {
+        handles.into_iter().map(|handle| self.get(handle))
+    }
+}
@@ -0,0 +1,304 @@
+//! Assemble blocks from block templates.
+//!
+//! [`BlockBuilder`] creates the coinbase transaction for a
+//! [`BlockTemplate`], and returns a block that is ready for grinding the
+//! nonce and submitting with `submitblock`.
+
+use std::collections::HashSet;
+
+use bitcoin::{
+    absolute::LockTime,
+    block,
+    hashes::Hash as _,
+    opcodes::all::OP_RETURN,
+    script::{self, PushBytesBuf},
+    transaction, Amount, Block, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+};
+
+use crate::client::{BlockTemplate, BlockTemplateTransaction, CoinbaseTxnOrValue};
+
+/// Maximum size of the coinbase `scriptSig`
+pub const MAX_COINBASE_SCRIPT_SIG_SIZE: usize = 100;
+
+/// Minimum size of the coinbase `scriptSig`
+const MIN_COINBASE_SCRIPT_SIG_SIZE: usize = 2;
+
+/// Witness reserved value used in the coinbase witness
+const WITNESS_RESERVED_VALUE: [u8; 32] = [0; 32];
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildBlockError {
+    #[error("block templates with `coinbasetxn` are not supported")]
+    CoinbaseTxnUnsupported,
+    #[error("failed to decode template transaction {index}")]
+    DecodeTransaction {
+        index: usize,
+        #[source]
+        source: bitcoin::consensus::encode::Error,
+    },
+    #[error("coinbase outputs ({outputs}) exceed the coinbase value ({coinbase_value})")]
+    OutputsExceedCoinbaseValue {
+        outputs: Amount,
+        coinbase_value: Amount,
+    },
+    #[error("coinbase transaction has no outputs")]
+    NoCoinbaseOutputs,
+    #[error(
+        "coinbase scriptSig is {size} bytes, which exceeds the maximum of {MAX_COINBASE_SCRIPT_SIG_SIZE} bytes"
+    )]
+    ScriptSigTooLarge { size: usize },
+    #[error("template time ({0}) does not fit in a block header")]
+    TimeOutOfRange(u64),
+}
+
+/// Returns `true` if the template requires a witness commitment
+fn segwit_active(template: &BlockTemplate) -> bool {
+    template
+        .rules
+        .iter()
+        .any(|rule| rule.trim_start_matches('!') == "segwit")
+}
+
+/// Builds a block from a [`BlockTemplate`].
+/// The coinbase transaction pays to the specified outputs, and includes
+/// any OP_RETURN commitments, and the witness commitment if required.
+/// Any coinbase value that is not claimed by an output is burned, so a
+/// payout script should usually be set with
+/// [`BlockBuilder::payout_script_pubkey`].
+#[derive(Clone, Debug)]
+pub struct BlockBuilder<'a> {
+    template: &'a BlockTemplate,
+    outputs: Vec<TxOut>,
+    payout_script_pubkey: Option<ScriptBuf>,
+    commitments: Vec<ScriptBuf>,
+    extranonce: Vec<u8>,
+    /// Indexes of template transactions that are excluded
+    excluded: HashSet<usize>,
+}
+
+impl<'a> BlockBuilder<'a> {
+    pub fn new(template: &'a BlockTemplate) -> Self {
+        Self {
+            template,
+            outputs: Vec::new(),
+            payout_script_pubkey: None,
+            commitments: Vec::new(),
+            extranonce: Vec::new(),
+            excluded: HashSet::new(),
+        }
+    }
+
+    /// Add a coinbase output
+    pub fn output(mut self, output: TxOut) -> Self {
+        self.outputs.push(output);
+        self
+    }
+
+    /// Pay the remaining coinbase value, after any outputs added with
+    /// [`Self::output`], to `script_pubkey`.
+    /// If no payout script is set, the remaining value is burned.
+    pub fn payout_script_pubkey(mut self, script_pubkey: ScriptBuf) -> Self {
+        self.payout_script_pubkey = Some(script_pubkey);
+        self
+    }
+
+    /// Add an OP_RETURN output committing to `data`
+    pub fn commitment(mut self, data: PushBytesBuf) -> Self {
+        let script_pubkey = script::Builder::new()
+            .push_opcode(OP_RETURN)
+            .push_slice(data)
+            .into_script();
+        self.commitments.push(script_pubkey);
+        self
+    }
+
+    /// Extranonce, pushed at the end of the coinbase `scriptSig`
+    pub fn extranonce(mut self, extranonce: Vec<u8>) -> Self {
+        self.extranonce = extranonce;
+        self
+    }
+
+    /// Only include template transactions for which `f` returns `true`.
+    /// Transactions that depend on an excluded transaction are also
+    /// excluded.
+    pub fn select_transactions<F>(mut self, mut f: F) -> Self
+    where
+        F: FnMut(&BlockTemplateTransaction) -> bool,
+    {
+        // Template transactions can only depend on earlier transactions
+        for (index, tx) in self.template.transactions.iter().enumerate() {
+            // Dependencies are 1-based indexes
+            let excluded_dependency = tx.depends.iter().any(|depends| {
+                depends
+                    .checked_sub(1)
+                    .is_some_and(|depends| self.excluded.contains(&(depends as usize)))
+            });
+            if excluded_dependency || !f(tx) {
+                self.excluded.insert(index);
+            }
+        }
+        self
+    }
+
+    /// Template transactions that are included in the block
+    pub fn transactions(&self) -> impl Iterator<Item = &'a BlockTemplateTransaction> + '_ {
+        self.template
+            .transactions
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !self.excluded.contains(index))
+            .map(|(_, tx)| tx)
+    }
+
+    /// Coinbase value, which is the block subsidy plus the fees of the
+    /// included transactions
+    pub fn coinbase_value(&self) -> Result<Amount, BuildBlockError> {
+        let CoinbaseTxnOrValue::ValueSats(template_value) = self.template.coinbase_txn_or_value
+        else {
+            return Err(BuildBlockError::CoinbaseTxnUnsupported);
+        };
+        let excluded_fees: i64 = self
+            .template
+            .transactions
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.excluded.contains(index))
+            .map(|(_, tx)| tx.fee.to_sat())
+            .sum();
+        let value = i64::try_from(template_value)
+            .unwrap_or(i64::MAX)
+            .saturating_sub(excluded_fees);
+        Ok(Amount::from_sat(value.max(0).unsigned_abs()))
+    }
+
+    /// Coinbase `scriptSig`, consisting of the BIP34 height, the coinbase
+    /// aux flags, and the extranonce
+    pub fn coinbase_script_sig(&self) -> Result<ScriptBuf, BuildBlockError> {
+        let mut builder = script::Builder::new().push_int(i64::from(self.template.height));
+        let flags: Vec<u8> = self
+            .template
+            .coinbase_aux
+            .values()
+            .flatten()
+            .copied()
+            .collect();
+        if !flags.is_empty() {
+            let size = flags.len();
+            let flags = PushBytesBuf::try_from(flags)
+                .map_err(|_| BuildBlockError::ScriptSigTooLarge { size })?;
+            builder = builder.push_slice(flags);
+        }
+        if !self.extranonce.is_empty() {
+            let extranonce = PushBytesBuf::try_from(self.extranonce.clone()).map_err(|_| {
+                BuildBlockError::ScriptSigTooLarge {
+                    size: self.extranonce.len(),
+                }
+            })?;
+            builder = builder.push_slice(extranonce);
+        }
+        let mut script_sig = builder.into_script();
+        if script_sig.len() < MIN_COINBASE_SCRIPT_SIG_SIZE {
+            script_sig.push_opcode(bitcoin::opcodes::OP_0);
+        }
+        if script_sig.len() > MAX_COINBASE_SCRIPT_SIG_SIZE {
+            return Err(BuildBlockError::ScriptSigTooLarge {
+                size: script_sig.len(),
+            });
+        }
+        Ok(script_sig)
+    }
+
+    /// Coinbase transaction, without the witness commitment
+    fn coinbase_without_commitment(&self) -> Result<Transaction, BuildBlockError> {
+        let coinbase_value = self.coinbase_value()?;
+        let mut output = self.outputs.clone();
+        let outputs_value = output
+            .iter()
+            .try_fold(Amount::ZERO, |total, output| {
+                total.checked_add(output.value)
+            })
+            .unwrap_or(Amount::MAX);
+        let remaining = coinbase_value.checked_sub(outputs_value).ok_or(
+            BuildBlockError::OutputsExceedCoinbaseValue {
+                outputs: outputs_value,
+                coinbase_value,
+            },
+        )?;
+        if let Some(script_pubkey) = &self.payout_script_pubkey {
+            output.push(TxOut {
+                value: remaining,
+                script_pubkey: script_pubkey.clone(),
+            });
+        }
+        output.extend(self.commitments.iter().map(|script_pubkey| TxOut {
+            value: Amount::ZERO,
+            script_pubkey: script_pubkey.clone(),
+        }));
+        // The witness commitment is added as an output later, if required
+        if output.is_empty() && !segwit_active(self.template) {
+            return Err(BuildBlockError::NoCoinbaseOutputs);
+        }
+        let witness = if segwit_active(self.template) {
+            Witness::from_slice(&[WITNESS_RESERVED_VALUE])
+        } else {
+            Witness::new()
+        };
+        Ok(Transaction {
+            version: transaction::Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: self.coinbase_script_sig()?,
+                sequence: Sequence::MAX,
+                witness,
+            }],
+            output,
+        })
+    }
+
+    /// Build the block, with a nonce of 0
+    pub fn build(&self) -> Result<Block, BuildBlockError> {
+        let time = u32::try_from(self.template.current_time)
+            .map_err(|_| BuildBlockError::TimeOutOfRange(self.template.current_time))?;
+        let mut txdata = vec![self.coinbase_without_commitment()?];
+        for (index, tx) in self.template.transactions.iter().enumerate() {
+            if self.excluded.contains(&index) {
+                continue;
+            }
+            let tx = bitcoin::consensus::deserialize(&tx.data)
+                .map_err(|source| BuildBlockError::DecodeTransaction { index, source })?;
+            txdata.push(tx);
+        }
+        let mut block = Block {
+            header: block::Header {
+                version: self.template.version,
+                prev_blockhash: self.template.prev_blockhash,
+                merkle_root: bitcoin::TxMerkleNode::all_zeros(),
+                time,
+                bits: self.template.compact_target,
+                nonce: 0,
+            },
+            txdata,
+        };
+        if segwit_active(self.template) {
+            // The witness root uses a wtxid of zero for the coinbase, so the
+            // commitment does not depend on the coinbase
+            let witness_root = block
+                .witness_root()
+                .expect("block contains a coinbase transaction");
+            let commitment =
+                Block::compute_witness_commitment(&witness_root, &WITNESS_RESERVED_VALUE);
+            let mut commitment_bytes = [0; 36];
+            commitment_bytes[..4].copy_from_slice(&[0xaa, 0x21, 0xa9, 0xed]);
+            commitment_bytes[4..].copy_from_slice(commitment.as_byte_array());
+            block.txdata[0].output.push(TxOut {
+                value: Amount::ZERO,
+                script_pubkey: ScriptBuf::new_op_return(commitment_bytes),
+            });
+        }
+        block.header.merkle_root = block
+            .compute_merkle_root()
+            .expect("block contains a coinbase transaction");
+        Ok(block)
+    }
+}
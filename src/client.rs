@@ -1,6 +1,8 @@
 use std::{
+    borrow::Cow,
     collections::{HashMap, HashSet},
     fmt::Debug,
+    future::Future,
     marker::PhantomData,
     ops::{Deref, DerefMut},
 };
@@ -61,20 +63,96 @@ impl From<bitcoin::CompactTarget> for CompactTargetRepr {
     }
 }
 
+/// Representation used with serde_with
+#[derive(Deserialize, Serialize)]
+#[repr(transparent)]
+#[serde(transparent)]
+struct WorkRepr(#[serde(with = "hex::serde")] [u8; 32]);
+
+impl From<WorkRepr> for bitcoin::Work {
+    fn from(repr: WorkRepr) -> Self {
+        Self::from_be_bytes(repr.0)
+    }
+}
+
+impl From<bitcoin::Work> for WorkRepr {
+    fn from(work: bitcoin::Work) -> Self {
+        Self(work.to_be_bytes())
+    }
+}
+
+/// Fee rate in sat/kvB, as used by Bitcoin Core.
+/// (De)serialized as BTC/kvB.
+/// Unlike [`bitcoin::FeeRate`], which counts sat/kwu, this represents
+/// every fee rate returned by Bitcoin Core exactly.
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
+#[repr(transparent)]
+#[serde(transparent)]
+pub struct FeeRatePerKvb(#[serde(with = "bitcoin::amount::serde::as_btc")] pub bitcoin::Amount);
+
+impl FeeRatePerKvb {
+    pub const ZERO: Self = Self(bitcoin::Amount::ZERO);
+
+    pub const fn from_sat_per_kvb(sat_per_kvb: u64) -> Self {
+        Self(bitcoin::Amount::from_sat(sat_per_kvb))
+    }
+
+    pub fn to_sat_per_kvb(self) -> u64 {
+        self.0.to_sat()
+    }
+
+    /// Convert to a [`bitcoin::FeeRate`], rounding up to the nearest sat/kwu.
+    /// This is lossy: 1 sat/kwu is 4 sat/kvB, so fee rates that are not a
+    /// multiple of 4 sat/kvB are rounded up.
+    pub fn to_fee_rate_ceil(self) -> bitcoin::FeeRate {
+        bitcoin::FeeRate::from_sat_per_kwu(self.to_sat_per_kvb().div_ceil(4))
+    }
+}
+
+/// Exact, since 1 sat/kwu is 4 sat/kvB
+impl From<bitcoin::FeeRate> for FeeRatePerKvb {
+    fn from(fee_rate: bitcoin::FeeRate) -> Self {
+        Self::from_sat_per_kvb(fee_rate.to_sat_per_kwu().saturating_mul(4))
+    }
+}
+
+/// Output from `getblockheader` where `verbose = true`
 #[serde_as]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Header {
     pub hash: BlockHash,
+    pub confirmations: isize, // Confirmations can be negative if block are reorged/invalidated
     pub height: u32,
     pub version: bitcoin::block::Version,
+    #[serde(rename = "versionHex")]
+    pub version_hex: String,
     #[serde(rename = "previousblockhash", default = "BlockHash::all_zeros")]
     pub prev_blockhash: BlockHash,
     #[serde(rename = "merkleroot")]
     pub merkle_root: bitcoin::TxMerkleNode,
     pub time: u32,
+    #[serde(rename = "mediantime")]
+    pub median_time: u32,
     #[serde_as(as = "FromInto<CompactTargetRepr>")]
     pub bits: bitcoin::CompactTarget,
     pub nonce: u32,
+    pub difficulty: f64,
+    /// Total work in the chain up to and including this block
+    #[serde(rename = "chainwork")]
+    #[serde_as(as = "FromInto<WorkRepr>")]
+    pub chain_work: bitcoin::Work,
+    /// Number of transactions in the block.
+    /// Not returned by some implementations.
+    #[serde(rename = "nTx", default, skip_serializing_if = "Option::is_none")]
+    pub n_tx: Option<u32>,
+    #[serde(
+        rename = "nextblockhash",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub next_blockhash: Option<BlockHash>,
 }
 
 impl Header {
@@ -83,7 +161,8 @@ impl Header {
         self.bits.into()
     }
 
-    /// Returns the total work of the block.
+    /// Returns the work of the block.
+    /// See [`Self::chain_work`] for the total work in the chain.
     pub fn work(&self) -> bitcoin::Work {
         self.target().to_work()
     }
@@ -122,10 +201,17 @@ pub struct MiningInfo {
 
 #[derive(Clone, Copy, Debug, Deserialize)]
 pub struct RawMempoolTxFees {
-    pub base: u64,
-    pub modified: u64,
-    pub ancestor: u64,
-    pub descendant: u64,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub base: bitcoin::Amount,
+    /// Fee including any fee delta from `prioritisetransaction`
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub modified: bitcoin::Amount,
+    /// Modified fees of the transaction and all in-mempool ancestors
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub ancestor: bitcoin::Amount,
+    /// Modified fees of the transaction and all in-mempool descendants
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub descendant: bitcoin::Amount,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -148,6 +234,10 @@ pub struct RawMempoolTxInfo {
     #[serde(rename = "bip125replaceable")]
     pub bip125_replaceable: bool,
     pub unbroadcast: bool,
+    /// Time that the transaction entered the mempool, as a unix timestamp
+    pub time: u64,
+    /// Block height when the transaction entered the mempool
+    pub height: u32,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -163,6 +253,191 @@ pub struct RawMempoolVerbose {
     pub entries: Vec<(Txid, RawMempoolTxInfo)>,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct MempoolInfo {
+    /// `true` if the mempool is fully loaded
+    pub loaded: bool,
+    /// Number of transactions
+    pub size: u64,
+    /// Sum of all virtual transaction sizes
+    pub bytes: u64,
+    /// Total memory usage
+    pub usage: u64,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub total_fee: bitcoin::Amount,
+    /// Maximum memory usage
+    #[serde(rename = "maxmempool")]
+    pub max_mempool: u64,
+    /// Minimum fee rate for a transaction to be accepted
+    #[serde(rename = "mempoolminfee")]
+    pub mempool_min_fee: FeeRatePerKvb,
+    #[serde(rename = "minrelaytxfee")]
+    pub min_relay_tx_fee: FeeRatePerKvb,
+    #[serde(rename = "incrementalrelayfee")]
+    pub incremental_relay_fee: Option<FeeRatePerKvb>,
+    /// Number of transactions that have not been seen on the network
+    #[serde(rename = "unbroadcastcount")]
+    pub unbroadcast_count: u64,
+    #[serde(rename = "fullrbf")]
+    pub full_rbf: Option<bool>,
+}
+
+/// Fees for a transaction accepted by `testmempoolaccept` or
+/// `submitpackage`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MempoolAcceptFees {
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub base: bitcoin::Amount,
+    /// Fee rate used to evaluate the transaction, which may be the package
+    /// fee rate
+    #[serde(rename = "effective-feerate")]
+    pub effective_fee_rate: Option<FeeRatePerKvb>,
+    /// Transactions whose fees and sizes are included in the effective fee
+    /// rate
+    #[serde(rename = "effective-includes", default)]
+    pub effective_includes: Vec<Wtxid>,
+}
+
+/// Result for a single transaction from `testmempoolaccept`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TestMempoolAcceptResult {
+    pub txid: Txid,
+    pub wtxid: Wtxid,
+    /// Package validation error, if any
+    #[serde(rename = "package-error")]
+    pub package_error: Option<String>,
+    /// Not returned if validation was not completed for the transaction,
+    /// eg. because another transaction in the package failed
+    pub allowed: Option<bool>,
+    pub vsize: Option<u64>,
+    /// Only returned if the transaction was allowed
+    pub fees: Option<MempoolAcceptFees>,
+    #[serde(rename = "reject-reason")]
+    pub reject_reason: Option<String>,
+    #[serde(rename = "reject-details")]
+    pub reject_details: Option<String>,
+}
+
+/// Result for a single transaction from `submitpackage`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SubmitPackageTxResult {
+    pub txid: Txid,
+    /// If a transaction with the same txid but a different witness was
+    /// already in the mempool, the wtxid of that transaction
+    #[serde(rename = "other-wtxid")]
+    pub other_wtxid: Option<Wtxid>,
+    pub vsize: Option<u64>,
+    pub fees: Option<MempoolAcceptFees>,
+    pub error: Option<String>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SubmitPackageResult {
+    /// `success` if the package was accepted
+    pub package_msg: String,
+    #[serde(rename = "tx-results")]
+    #[serde_as(as = "Map<_, _>")]
+    pub tx_results: Vec<(Wtxid, SubmitPackageTxResult)>,
+    /// Transactions that were replaced by the package
+    #[serde(rename = "replaced-transactions", default)]
+    pub replaced_transactions: Vec<Txid>,
+}
+
+/// Fee estimate mode for `estimatesmartfee`
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EstimateMode {
+    /// Use the default mode, which is currently `CONSERVATIVE`
+    Unset,
+    /// Responds faster to short-term drops in fee rates
+    Economical,
+    /// Considers a longer history of blocks, so is less likely to
+    /// underestimate
+    Conservative,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EstimateSmartFeeResult {
+    /// Not returned if there is insufficient data.
+    #[serde(rename = "feerate")]
+    pub fee_rate: Option<FeeRatePerKvb>,
+    #[serde(default)]
+    pub errors: Vec<String>,
+    /// Block target for which the estimate is valid, which may differ from
+    /// the requested target
+    pub blocks: u32,
+}
+
+impl EstimateSmartFeeResult {
+    /// The estimated fee rate as a [`bitcoin::FeeRate`], rounded up to the
+    /// nearest sat/kwu so that paying it never falls below the estimate.
+    /// Use [`Self::fee_rate`] for the exact sat/kvB value.
+    pub fn bitcoin_fee_rate(&self) -> Option<bitcoin::FeeRate> {
+        self.fee_rate.map(FeeRatePerKvb::to_fee_rate_ceil)
+    }
+}
+
+/// Statistics for a range of fee rates, from `estimaterawfee`.
+/// Fee rates are in sat/kvB.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FeeRateBucket {
+    #[serde(rename = "startrange")]
+    pub start_range: f64,
+    #[serde(rename = "endrange")]
+    pub end_range: f64,
+    /// Number of transactions that confirmed within the target
+    #[serde(rename = "withintarget")]
+    pub within_target: f64,
+    /// Number of transactions that confirmed at any time
+    #[serde(rename = "totalconfirmed")]
+    pub total_confirmed: f64,
+    /// Number of transactions in the mempool for longer than the target
+    #[serde(rename = "inmempool")]
+    pub in_mempool: f64,
+    /// Number of transactions that left the mempool without confirming
+    /// within the target
+    #[serde(rename = "leftmempool")]
+    pub left_mempool: f64,
+}
+
+/// Fee estimate for a single horizon, from `estimaterawfee`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RawFeeEstimate {
+    /// Not returned if there is insufficient data.
+    #[serde(rename = "feerate")]
+    pub fee_rate: Option<FeeRatePerKvb>,
+    /// Exponential decay per block for historical moving average
+    pub decay: f64,
+    /// Number of blocks for each bucket of confirmation targets
+    pub scale: u32,
+    /// Lowest fee rate range that passed the success threshold
+    pub pass: Option<FeeRateBucket>,
+    /// Highest fee rate range that failed the success threshold
+    pub fail: Option<FeeRateBucket>,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+impl RawFeeEstimate {
+    /// The estimated fee rate as a [`bitcoin::FeeRate`], rounded up to the
+    /// nearest sat/kwu so that paying it never falls below the estimate.
+    /// Use [`Self::fee_rate`] for the exact sat/kvB value.
+    pub fn bitcoin_fee_rate(&self) -> Option<bitcoin::FeeRate> {
+        self.fee_rate.map(FeeRatePerKvb::to_fee_rate_ceil)
+    }
+}
+
+/// Output from `estimaterawfee`.
+/// Horizons are only returned if the confirmation target is tracked for
+/// that horizon.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EstimateRawFeeResult {
+    pub short: Option<RawFeeEstimate>,
+    pub medium: Option<RawFeeEstimate>,
+    pub long: Option<RawFeeEstimate>,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct TxOutSetInfo {
     pub height: u32,
@@ -191,13 +466,176 @@ pub struct NetworkInfo {
     pub time_offset_s: i64,
 }
 
-/// Output from `getrawtransaction` where `verbosity = 1`
+/// `scriptSig` field of a transaction input
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScriptSig {
+    pub asm: String,
+    pub hex: bitcoin::ScriptBuf,
+}
+
+/// Output spent by a transaction input, as returned by `getrawtransaction`
+/// where `verbosity = 2` and `getblock` where `verbosity = 3`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Prevout {
+    /// `true` if the output was created by a coinbase transaction
+    pub generated: bool,
+    /// Height of the block that created the output
+    pub height: u32,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub value: bitcoin::Amount,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: ScriptPubKey,
+}
+
+/// Transaction input, as returned by `getrawtransaction` where
+/// `verbosity = 1`
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TxInInfo {
+    /// Coinbase script. Only present for coinbase inputs.
+    #[serde_as(as = "Option<serde_with::hex::Hex>")]
+    #[serde(default)]
+    pub coinbase: Option<Vec<u8>>,
+    /// Not present for coinbase inputs
+    pub txid: Option<Txid>,
+    /// Not present for coinbase inputs
+    pub vout: Option<u32>,
+    /// Not present for coinbase inputs
+    #[serde(rename = "scriptSig")]
+    pub script_sig: Option<ScriptSig>,
+    #[serde(rename = "txinwitness", default)]
+    pub witness: bitcoin::Witness,
+    pub sequence: bitcoin::Sequence,
+    /// Only present in `getrawtransaction` output where `verbosity = 2`, and
+    /// `getblock` output where `verbosity = 3`.
+    /// Omitted for coinbase inputs, and if block undo data is not available.
+    pub prevout: Option<Prevout>,
+}
+
+impl TxInInfo {
+    pub fn is_coinbase(&self) -> bool {
+        self.coinbase.is_some()
+    }
+
+    /// Returns `None` for coinbase inputs
+    pub fn previous_output(&self) -> Option<bitcoin::OutPoint> {
+        match (self.txid, self.vout) {
+            (Some(txid), Some(vout)) => Some(bitcoin::OutPoint { txid, vout }),
+            _ => None,
+        }
+    }
+}
+
+/// Script type, as returned in the `type` field of a `scriptPubKey`
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptPubKeyType {
+    Nonstandard,
+    Anchor,
+    Pubkey,
+    Pubkeyhash,
+    Scripthash,
+    Multisig,
+    Nulldata,
+    #[serde(rename = "witness_v0_keyhash")]
+    WitnessV0Keyhash,
+    #[serde(rename = "witness_v0_scripthash")]
+    WitnessV0Scripthash,
+    #[serde(rename = "witness_v1_taproot")]
+    WitnessV1Taproot,
+    WitnessUnknown,
+}
+
+/// `scriptPubKey` field of a transaction output
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScriptPubKey {
+    pub asm: String,
+    /// Inferred output descriptor
+    pub desc: Option<String>,
+    pub hex: bitcoin::ScriptBuf,
+    /// Only present if the script has a corresponding address
+    pub address: Option<bitcoin::Address<bitcoin::address::NetworkUnchecked>>,
+    #[serde(rename = "type")]
+    pub script_type: ScriptPubKeyType,
+}
+
+/// Transaction output, as returned by `getrawtransaction` where
+/// `verbosity = 1`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TxOutInfo {
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub value: bitcoin::Amount,
+    pub n: u32,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: ScriptPubKey,
+}
+
+/// Output from `getrawtransaction` where `verbosity = 1`,
+/// and transactions in the output of `getblock` where `verbosity = 2`
 #[derive(Clone, Debug, Deserialize)]
 pub struct TxInfo {
     #[serde(deserialize_with = "hex::serde::deserialize")]
     pub hex: Vec<u8>,
     pub txid: Txid,
-    // TODO: add more fields
+    #[serde(rename = "hash")]
+    pub wtxid: Wtxid,
+    pub size: u64,
+    pub vsize: u64,
+    pub weight: Weight,
+    pub version: bitcoin::transaction::Version,
+    #[serde(rename = "locktime")]
+    pub lock_time: bitcoin::absolute::LockTime,
+    pub vin: Vec<TxInInfo>,
+    pub vout: Vec<TxOutInfo>,
+    /// Only present in `getrawtransaction` output where `verbosity = 2`, and
+    /// `getblock` output where `verbosity >= 2`.
+    /// Omitted for coinbase transactions, and if block undo data is not
+    /// available.
+    #[serde(default, with = "bitcoin::amount::serde::as_btc::opt")]
+    pub fee: Option<bitcoin::Amount>,
+    /// Only present in `getrawtransaction` output, if the `blockhash`
+    /// argument was specified
+    pub in_active_chain: Option<bool>,
+    /// Only present in `getrawtransaction` output, if the transaction is in a
+    /// block
+    #[serde(rename = "blockhash")]
+    pub block_hash: Option<BlockHash>,
+    /// Only present in `getrawtransaction` output, if the transaction is in a
+    /// block
+    pub confirmations: Option<u32>,
+    /// Only present in `getrawtransaction` output, if the transaction is in a
+    /// block
+    pub time: Option<u64>,
+    /// Only present in `getrawtransaction` output, if the transaction is in a
+    /// block
+    #[serde(rename = "blocktime")]
+    pub block_time: Option<u64>,
+}
+
+impl TxInfo {
+    /// Decode the transaction from [`Self::hex`]
+    pub fn transaction(&self) -> Result<bitcoin::Transaction, bitcoin::consensus::encode::Error> {
+        bitcoin::consensus::deserialize(&self.hex)
+    }
+
+    /// Total value of the outputs spent by the transaction.
+    /// Returns `None` unless prevouts are available for every input.
+    pub fn input_value(&self) -> Option<bitcoin::Amount> {
+        self.vin
+            .iter()
+            .try_fold(bitcoin::Amount::ZERO, |total, txin| {
+                total.checked_add(txin.prevout.as_ref()?.value)
+            })
+    }
+
+    /// Total value of the transaction outputs
+    pub fn output_value(&self) -> Option<bitcoin::Amount> {
+        self.vout
+            .iter()
+            .try_fold(bitcoin::Amount::ZERO, |total, txout| {
+                total.checked_add(txout.value)
+            })
+    }
 }
 
 mod private {
@@ -273,25 +711,66 @@ impl TryFrom<&Block<true>> for bitcoin::Block {
         let txdata = block
             .tx
             .iter()
-            .map(|tx_info| bitcoin::consensus::deserialize(&tx_info.hex))
+            .map(TxInfo::transaction)
             .collect::<Result<_, _>>()?;
         Ok(Self { header, txdata })
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// `getblocktemplate` request mode, as described in BIP22 and BIP23
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockTemplateMode {
+    Template,
+    /// Check a block proposal, without submitting it
+    Proposal,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct BlockTemplateRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<BlockTemplateMode>,
     #[serde(default)]
     pub rules: Vec<String>,
     #[serde(default)]
     pub capabilities: HashSet<String>,
+    /// Wait until the template identified by the long poll ID is stale
+    #[serde(
+        default,
+        rename = "longpollid",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub long_poll_id: Option<String>,
+    /// Serialized block, in proposal mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde_as(as = "Option<serde_with::hex::Hex>")]
+    pub data: Option<Vec<u8>>,
+    /// Work ID from the template, in proposal mode
+    #[serde(default, rename = "workid", skip_serializing_if = "Option::is_none")]
+    pub work_id: Option<String>,
+}
+
+impl BlockTemplateRequest {
+    /// Request to check `block` in proposal mode
+    pub fn proposal(block: &bitcoin::Block) -> Self {
+        Self {
+            mode: Some(BlockTemplateMode::Proposal),
+            data: Some(bitcoin::consensus::serialize(block)),
+            ..Self::default()
+        }
+    }
 }
 
 impl Default for BlockTemplateRequest {
     fn default() -> Self {
         Self {
+            mode: None,
             rules: vec!["segwit".into()],
             capabilities: HashSet::new(),
+            long_poll_id: None,
+            data: None,
+            work_id: None,
         }
     }
 }
@@ -417,6 +896,657 @@ pub struct AddressInfo {
     pub hd_seed_id: Option<String>,
 }
 
+/// Result of `createwallet` and `loadwallet`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LoadWalletResult {
+    pub name: String,
+    /// Warning message, before v25
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    /// Warning messages, since v25
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UnloadWalletResult {
+    /// Warning message, before v25
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    /// Warning messages, since v25
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WalletDirEntry {
+    pub name: String,
+    /// Warnings for wallets that could not be fully loaded, since v28
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// Result of `listwalletdir`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WalletDir {
+    pub wallets: Vec<WalletDirEntry>,
+}
+
+/// Wallet rescan status
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum WalletScanning {
+    NotScanning(monostate::MustBe!(false)),
+    Scanning {
+        /// Elapsed seconds since the scan started
+        duration: u64,
+        /// Scanning progress percentage, between 0 and 1
+        progress: f64,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LastProcessedBlock {
+    pub hash: BlockHash,
+    pub height: u32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct WalletInfo {
+    #[serde(rename = "walletname")]
+    pub wallet_name: String,
+    #[serde(rename = "walletversion")]
+    pub wallet_version: u64,
+    /// Database format, eg. `sqlite`
+    pub format: String,
+    /// Removed in v30
+    #[serde(default, with = "bitcoin::amount::serde::as_btc::opt")]
+    pub balance: Option<bitcoin::Amount>,
+    #[serde(rename = "txcount")]
+    pub tx_count: u64,
+    #[serde(rename = "keypoololdest")]
+    pub keypool_oldest: Option<u64>,
+    #[serde(rename = "keypoolsize")]
+    pub keypool_size: u64,
+    #[serde(rename = "keypoolsize_hd_internal")]
+    pub keypool_size_hd_internal: Option<u64>,
+    /// Unix timestamp until which the wallet is unlocked, or 0 if locked.
+    /// Omitted for unencrypted wallets.
+    pub unlocked_until: Option<u64>,
+    #[serde(default, rename = "paytxfee")]
+    pub pay_tx_fee: Option<FeeRatePerKvb>,
+    #[serde(rename = "hdseedid")]
+    pub hd_seed_id: Option<String>,
+    pub private_keys_enabled: bool,
+    pub avoid_reuse: bool,
+    pub scanning: WalletScanning,
+    pub descriptors: bool,
+    pub external_signer: bool,
+    /// Since v26
+    pub blank: Option<bool>,
+    /// Earliest time that the wallet may have received transactions
+    pub birthtime: Option<u64>,
+    /// Since v26
+    #[serde(rename = "lastprocessedblock")]
+    pub last_processed_block: Option<LastProcessedBlock>,
+}
+
+/// Wrapper for (de)serializing a PSBT as base64
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(transparent)]
+pub struct Base64Psbt(pub bitcoin::Psbt);
+
+impl From<bitcoin::Psbt> for Base64Psbt {
+    fn from(psbt: bitcoin::Psbt) -> Self {
+        Self(psbt)
+    }
+}
+
+impl From<Base64Psbt> for bitcoin::Psbt {
+    fn from(psbt: Base64Psbt) -> Self {
+        psbt.0
+    }
+}
+
+impl<'de> Deserialize<'de> for Base64Psbt {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use base64::Engine as _;
+        use serde::de::Error as _;
+        let encoded = String::deserialize(deserializer)?;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(D::Error::custom)?;
+        bitcoin::Psbt::deserialize(&bytes)
+            .map(Self)
+            .map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for Base64Psbt {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use base64::Engine as _;
+        base64::engine::general_purpose::STANDARD
+            .encode(self.0.serialize())
+            .serialize(serializer)
+    }
+}
+
+/// Input for `walletcreatefundedpsbt`
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CreateRawTxInput {
+    pub txid: Txid,
+    pub vout: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<bitcoin::Sequence>,
+}
+
+/// Output for `walletcreatefundedpsbt`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CreateRawTxOutput {
+    Address {
+        address: bitcoin::Address<bitcoin::address::NetworkUnchecked>,
+        amount: bitcoin::Amount,
+    },
+    /// OP_RETURN output with the specified data
+    Data(Vec<u8>),
+}
+
+impl Serialize for CreateRawTxOutput {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeMap as _;
+
+        #[derive(Serialize)]
+        #[serde(transparent)]
+        struct Btc(#[serde(with = "bitcoin::amount::serde::as_btc")] bitcoin::Amount);
+
+        let mut map = serializer.serialize_map(Some(1))?;
+        match self {
+            Self::Address { address, amount } => {
+                map.serialize_entry(address, &Btc(*amount))?;
+            }
+            Self::Data(data) => {
+                map.serialize_entry("data", &hex::encode(data))?;
+            }
+        }
+        map.end()
+    }
+}
+
+/// Options for `walletcreatefundedpsbt`.
+/// Unset options use the wallet's defaults.
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct WalletCreateFundedPsbtOptions {
+    /// Add inputs if the specified inputs are insufficient
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub add_inputs: Option<bool>,
+    #[serde(rename = "changeAddress", skip_serializing_if = "Option::is_none")]
+    pub change_address: Option<bitcoin::Address<bitcoin::address::NetworkUnchecked>>,
+    #[serde(rename = "changePosition", skip_serializing_if = "Option::is_none")]
+    pub change_position: Option<u32>,
+    /// Output type of the change output, eg. `bech32m`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_type: Option<String>,
+    #[serde(rename = "includeWatching", skip_serializing_if = "Option::is_none")]
+    pub include_watching: Option<bool>,
+    #[serde(rename = "lockUnspents", skip_serializing_if = "Option::is_none")]
+    pub lock_unspents: Option<bool>,
+    #[serde(rename = "feeRate", skip_serializing_if = "Option::is_none")]
+    #[serde_as(as = "Option<FromInto<FeeRatePerKvb>>")]
+    pub fee_rate: Option<bitcoin::FeeRate>,
+    /// Indexes of outputs that the fee is deducted from
+    #[serde(
+        rename = "subtractFeeFromOutputs",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub subtract_fee_from_outputs: Vec<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaceable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conf_target: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate_mode: Option<EstimateMode>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WalletCreateFundedPsbtResult {
+    #[serde_as(as = "FromInto<Base64Psbt>")]
+    pub psbt: bitcoin::Psbt,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub fee: bitcoin::Amount,
+    /// Position of the change output, or -1 if there is no change output
+    #[serde(rename = "changepos")]
+    pub change_position: i32,
+}
+
+/// Result of `walletprocesspsbt`
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WalletProcessPsbtResult {
+    #[serde_as(as = "FromInto<Base64Psbt>")]
+    pub psbt: bitcoin::Psbt,
+    /// `true` if the transaction has a complete set of signatures
+    pub complete: bool,
+    /// Extracted transaction, if complete and finalized. Since v26.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde_as(as = "Option<serde_with::hex::Hex>")]
+    pub hex: Option<Vec<u8>>,
+}
+
+/// Result of `finalizepsbt`
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FinalizePsbtResult {
+    /// Present if the transaction was not extracted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde_as(as = "Option<FromInto<Base64Psbt>>")]
+    pub psbt: Option<bitcoin::Psbt>,
+    /// Present if the transaction was extracted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde_as(as = "Option<serde_with::hex::Hex>")]
+    pub hex: Option<Vec<u8>>,
+    /// `true` if the transaction has a complete set of signatures
+    pub complete: bool,
+}
+
+impl FinalizePsbtResult {
+    /// Decode the extracted transaction from [`Self::hex`]
+    pub fn transaction(
+        &self,
+    ) -> Option<Result<bitcoin::Transaction, bitcoin::consensus::encode::Error>> {
+        self.hex.as_deref().map(bitcoin::consensus::deserialize)
+    }
+}
+
+/// Decoded transaction, as returned by `decoderawtransaction`.
+/// Unlike [`TxInfo`], the transaction hex is not included.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DecodedTxInfo {
+    pub txid: Txid,
+    #[serde(rename = "hash")]
+    pub wtxid: Wtxid,
+    pub size: u64,
+    pub vsize: u64,
+    pub weight: Weight,
+    pub version: bitcoin::transaction::Version,
+    #[serde(rename = "locktime")]
+    pub lock_time: bitcoin::absolute::LockTime,
+    pub vin: Vec<TxInInfo>,
+    pub vout: Vec<TxOutInfo>,
+}
+
+/// Result of `decodepsbt`.
+/// Global xpubs, proprietary fields, inputs and outputs are not included.
+/// Use the fields of the decoded [`bitcoin::Psbt`] instead, eg.
+/// [`bitcoin::Psbt::inputs`] for UTXOs, partial signatures, scripts and
+/// derivation paths.
+#[derive(Clone, Debug, Deserialize)]
+pub struct DecodePsbtResult {
+    /// Decoded unsigned transaction
+    pub tx: DecodedTxInfo,
+    pub psbt_version: u32,
+    #[serde(default)]
+    pub unknown: HashMap<String, String>,
+    /// Present if all inputs have UTXO info
+    #[serde(default, with = "bitcoin::amount::serde::as_btc::opt")]
+    pub fee: Option<bitcoin::Amount>,
+}
+
+/// BIP174 role
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PsbtRole {
+    Creator,
+    Updater,
+    Signer,
+    Finalizer,
+    Extractor,
+}
+
+/// Data that is missing for an input to be finalized
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct PsbtMissing {
+    /// Hashes of public keys for which BIP32 derivation paths are missing
+    #[serde(default)]
+    pub pubkeys: Vec<bitcoin::PubkeyHash>,
+    /// Hashes of public keys for which signatures are missing
+    #[serde(default)]
+    pub signatures: Vec<bitcoin::PubkeyHash>,
+    /// Hash of the missing redeem script
+    #[serde(rename = "redeemscript")]
+    pub redeem_script: Option<bitcoin::ScriptHash>,
+    /// Hash of the missing witness script
+    #[serde(rename = "witnessscript")]
+    pub witness_script: Option<bitcoin::WScriptHash>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct AnalyzePsbtInput {
+    pub has_utxo: bool,
+    pub is_final: bool,
+    pub missing: Option<PsbtMissing>,
+    /// Role of the next participant, if the input is not final
+    pub next: Option<PsbtRole>,
+}
+
+/// Result of `analyzepsbt`
+#[derive(Clone, Debug, Deserialize)]
+pub struct AnalyzePsbtResult {
+    #[serde(default)]
+    pub inputs: Vec<AnalyzePsbtInput>,
+    /// Present if all inputs have UTXO info
+    pub estimated_vsize: Option<u64>,
+    /// Present if all inputs have UTXO info
+    #[serde(default)]
+    pub estimated_feerate: Option<FeeRatePerKvb>,
+    /// Present if all inputs have UTXO info
+    #[serde(default, with = "bitcoin::amount::serde::as_btc::opt")]
+    pub fee: Option<bitcoin::Amount>,
+    /// Role of the next participant
+    pub next: PsbtRole,
+    /// Present if the PSBT is invalid
+    pub error: Option<String>,
+}
+
+/// Result of `getdescriptorinfo`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DescriptorInfo {
+    /// Canonical descriptor, with public keys only and a checksum
+    pub descriptor: String,
+    /// Checksum for the descriptor argument
+    pub checksum: String,
+    #[serde(rename = "isrange")]
+    pub is_range: bool,
+    #[serde(rename = "issolvable")]
+    pub is_solvable: bool,
+    #[serde(rename = "hasprivatekeys")]
+    pub has_private_keys: bool,
+}
+
+/// Inclusive range of indexes for a ranged descriptor
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DescriptorRange(pub u32, pub u32);
+
+impl From<std::ops::RangeInclusive<u32>> for DescriptorRange {
+    fn from(range: std::ops::RangeInclusive<u32>) -> Self {
+        Self(*range.start(), *range.end())
+    }
+}
+
+/// Time from which to rescan for an imported descriptor
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImportTimestamp {
+    /// Do not rescan
+    Now,
+    /// Unix timestamp
+    Time(u64),
+}
+
+impl Serialize for ImportTimestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Now => serializer.serialize_str("now"),
+            Self::Time(time) => serializer.serialize_u64(*time),
+        }
+    }
+}
+
+/// Request for `importdescriptors`
+#[derive(Clone, Debug, Serialize)]
+pub struct ImportDescriptorRequest {
+    #[serde(rename = "desc")]
+    pub descriptor: String,
+    /// Use the descriptor for new addresses. Must be ranged.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<DescriptorRange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_index: Option<u32>,
+    pub timestamp: ImportTimestamp,
+    /// Use the descriptor for change addresses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub internal: Option<bool>,
+    /// Not allowed for internal or ranged descriptors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl ImportDescriptorRequest {
+    pub fn new(descriptor: impl Into<String>, timestamp: ImportTimestamp) -> Self {
+        Self {
+            descriptor: descriptor.into(),
+            active: None,
+            range: None,
+            next_index: None,
+            timestamp,
+            internal: None,
+            label: None,
+        }
+    }
+}
+
+/// Result for each request in `importdescriptors`
+#[derive(Clone, Debug, Deserialize)]
+pub struct ImportDescriptorResult {
+    pub success: bool,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    pub error: Option<jsonrpsee::types::ErrorObjectOwned>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ListedDescriptor {
+    #[serde(rename = "desc")]
+    pub descriptor: String,
+    /// Unix timestamp from which the descriptor was imported
+    pub timestamp: u64,
+    pub active: bool,
+    /// Only present for active descriptors
+    pub internal: Option<bool>,
+    /// Only present for ranged descriptors
+    pub range: Option<DescriptorRange>,
+    /// Next index to generate addresses from, for ranged descriptors
+    pub next_index: Option<u32>,
+}
+
+/// Result of `listdescriptors`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ListDescriptorsResult {
+    pub wallet_name: String,
+    pub descriptors: Vec<ListedDescriptor>,
+}
+
+/// Category of a wallet transaction entry
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionCategory {
+    /// Sent from the wallet
+    Send,
+    /// Received by the wallet
+    Receive,
+    /// Mature coinbase output
+    Generate,
+    /// Immature coinbase output
+    Immature,
+    /// Coinbase output from a block that is not in the best chain
+    Orphan,
+}
+
+/// BIP125 replaceability of a wallet transaction
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bip125Replaceable {
+    Yes,
+    No,
+    /// The transaction is unconfirmed, and not in the mempool
+    Unknown,
+}
+
+/// Wallet transaction info, included in `listtransactions`,
+/// `listsinceblock` and `gettransaction` entries
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WalletTxInfo {
+    /// Negative if the transaction conflicts with a transaction in the best
+    /// chain
+    pub confirmations: i64,
+    /// `true` for coinbase transactions
+    #[serde(default)]
+    pub generated: bool,
+    /// Only present for unconfirmed transactions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trusted: Option<bool>,
+    #[serde(default, rename = "blockhash", skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<BlockHash>,
+    #[serde(
+        default,
+        rename = "blockheight",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub block_height: Option<u32>,
+    /// Index of the transaction in the block
+    #[serde(
+        default,
+        rename = "blockindex",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub block_index: Option<u32>,
+    #[serde(default, rename = "blocktime", skip_serializing_if = "Option::is_none")]
+    pub block_time: Option<u64>,
+    pub txid: Txid,
+    /// Since v24
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wtxid: Option<Wtxid>,
+    /// Wallet transactions that spend the same inputs
+    #[serde(rename = "walletconflicts")]
+    pub wallet_conflicts: Vec<Txid>,
+    /// Only present if the transaction was replaced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaced_by_txid: Option<Txid>,
+    /// Only present if the transaction replaced another transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaces_txid: Option<Txid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Unix timestamp of the transaction, or of the block if earlier
+    pub time: u64,
+    /// Unix timestamp when the transaction was received
+    #[serde(rename = "timereceived")]
+    pub time_received: u64,
+    #[serde(rename = "bip125-replaceable")]
+    pub bip125_replaceable: Bip125Replaceable,
+    /// Descriptors for the outputs, since v24
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parent_descs: Vec<String>,
+}
+
+/// Output sent or received by the wallet, in `listtransactions`,
+/// `listsinceblock` and `gettransaction` entries
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WalletTxOutputEntry {
+    #[serde(rename = "involvesWatchonly", default)]
+    pub involves_watch_only: bool,
+    /// Omitted for outputs without an address, eg. OP_RETURN outputs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<bitcoin::Address<bitcoin::address::NetworkUnchecked>>,
+    pub category: TransactionCategory,
+    /// Negative for [`TransactionCategory::Send`]
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub amount: bitcoin::SignedAmount,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub vout: u32,
+    /// Negative fee, only present for [`TransactionCategory::Send`]
+    #[serde(
+        default,
+        with = "bitcoin::amount::serde::as_btc::opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub fee: Option<bitcoin::SignedAmount>,
+    /// Only present for [`TransactionCategory::Send`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abandoned: Option<bool>,
+}
+
+/// Entry in `listtransactions` and `listsinceblock`, for each output that
+/// the wallet sent or received
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ListTransactionsEntry {
+    #[serde(flatten)]
+    pub output: WalletTxOutputEntry,
+    #[serde(flatten)]
+    pub info: WalletTxInfo,
+}
+
+/// Result of `listsinceblock`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ListSinceBlockResult {
+    pub transactions: Vec<ListTransactionsEntry>,
+    /// Transactions that were removed from the best chain by a reorg, if
+    /// `include_removed` was not `false`
+    #[serde(default)]
+    pub removed: Vec<ListTransactionsEntry>,
+    /// Block hash to use as the `blockhash` argument for the next call
+    #[serde(rename = "lastblock")]
+    pub last_block: BlockHash,
+}
+
+/// Output sent or received by the wallet, in `gettransaction`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetTransactionDetail {
+    #[serde(flatten)]
+    pub output: WalletTxOutputEntry,
+    /// Descriptors for the output, since v24
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parent_descs: Vec<String>,
+}
+
+/// Result of `gettransaction`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetTransactionResult {
+    /// Net amount received by the wallet, excluding fees
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub amount: bitcoin::SignedAmount,
+    /// Negative fee, only present if the wallet sent the transaction
+    #[serde(
+        default,
+        with = "bitcoin::amount::serde::as_btc::opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub fee: Option<bitcoin::SignedAmount>,
+    #[serde(flatten)]
+    pub info: WalletTxInfo,
+    pub details: Vec<GetTransactionDetail>,
+    #[serde(
+        rename = "hex",
+        with = "bitcoin::consensus::serde::With::<bitcoin::consensus::serde::Hex>"
+    )]
+    pub tx: bitcoin::Transaction,
+    /// Decoded transaction, if `verbose` was `true`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decoded: Option<DecodedTxInfo>,
+    /// Since v26
+    #[serde(
+        default,
+        rename = "lastprocessedblock",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_processed_block: Option<LastProcessedBlock>,
+}
+
 /// Additional blockchain info, present after v29
 #[serde_as]
 #[derive(Debug, Deserialize)]
@@ -455,6 +1585,153 @@ pub struct ZMQNotification {
     pub high_water_mark: u32,
 }
 
+/// Result of `submitblock`, or of `getblocktemplate` in proposal mode, as
+/// described in BIP22.
+/// Rejection reasons other than the BIP22 reasons are block validation
+/// errors from Bitcoin Core.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SubmitBlockResult {
+    /// The block was accepted (`null`)
+    Accepted,
+    /// The block was already known
+    Duplicate,
+    /// The block was already known, and is invalid
+    DuplicateInvalid,
+    /// The block was already known, but not validated
+    DuplicateInconclusive,
+    /// The block was accepted, but is not on the best chain
+    Inconclusive,
+    /// Rejected for an unspecified reason
+    Rejected,
+    /// Proof of work does not meet the target
+    HighHash,
+    BadTxnMrklRoot,
+    BadDiffBits,
+    BadPrevBlk,
+    PrevBlkNotFound,
+    /// Block version is obsolete, eg. `bad-version(0x00000001)`
+    BadVersion(block::Version),
+    TimeTooOld,
+    TimeTooNew,
+    BadCbMissing,
+    BadCbHeight,
+    BadCbAmount,
+    BadWitnessNonceSize,
+    BadWitnessMerkleMatch,
+    BadBlkLength,
+    BadBlkWeight,
+    BadBlkSigops,
+    /// Any other rejection reason
+    Other(String),
+}
+
+impl SubmitBlockResult {
+    /// Reason string, or `None` if accepted
+    pub fn reason(&self) -> Option<Cow<'_, str>> {
+        let res = match self {
+            Self::Accepted => return None,
+            Self::Duplicate => "duplicate",
+            Self::DuplicateInvalid => "duplicate-invalid",
+            Self::DuplicateInconclusive => "duplicate-inconclusive",
+            Self::Inconclusive => "inconclusive",
+            Self::Rejected => "rejected",
+            Self::HighHash => "high-hash",
+            Self::BadTxnMrklRoot => "bad-txnmrklroot",
+            Self::BadDiffBits => "bad-diffbits",
+            Self::BadPrevBlk => "bad-prevblk",
+            Self::PrevBlkNotFound => "prev-blk-not-found",
+            Self::BadVersion(version) => {
+                return Some(Cow::Owned(format!(
+                    "bad-version(0x{:08x})",
+                    version.to_consensus()
+                )))
+            }
+            Self::TimeTooOld => "time-too-old",
+            Self::TimeTooNew => "time-too-new",
+            Self::BadCbMissing => "bad-cb-missing",
+            Self::BadCbHeight => "bad-cb-height",
+            Self::BadCbAmount => "bad-cb-amount",
+            Self::BadWitnessNonceSize => "bad-witness-nonce-size",
+            Self::BadWitnessMerkleMatch => "bad-witness-merkle-match",
+            Self::BadBlkLength => "bad-blk-length",
+            Self::BadBlkWeight => "bad-blk-weight",
+            Self::BadBlkSigops => "bad-blk-sigops",
+            Self::Other(reason) => reason,
+        };
+        Some(Cow::Borrowed(res))
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, Self::Accepted)
+    }
+}
+
+impl From<Option<String>> for SubmitBlockResult {
+    fn from(reason: Option<String>) -> Self {
+        let Some(reason) = reason else {
+            return Self::Accepted;
+        };
+        // The version is included in the reason, eg. `bad-version(0x00000001)`
+        if let Some(version) = reason
+            .strip_prefix("bad-version(0x")
+            .and_then(|version| version.strip_suffix(')'))
+            .and_then(|version| u32::from_str_radix(version, 16).ok())
+        {
+            return Self::BadVersion(block::Version::from_consensus(version as i32));
+        }
+        match reason.as_str() {
+            "duplicate" => Self::Duplicate,
+            "duplicate-invalid" => Self::DuplicateInvalid,
+            "duplicate-inconclusive" => Self::DuplicateInconclusive,
+            "inconclusive" => Self::Inconclusive,
+            "rejected" => Self::Rejected,
+            "high-hash" => Self::HighHash,
+            "bad-txnmrklroot" => Self::BadTxnMrklRoot,
+            "bad-diffbits" => Self::BadDiffBits,
+            "bad-prevblk" => Self::BadPrevBlk,
+            "prev-blk-not-found" => Self::PrevBlkNotFound,
+            "time-too-old" => Self::TimeTooOld,
+            "time-too-new" => Self::TimeTooNew,
+            "bad-cb-missing" => Self::BadCbMissing,
+            "bad-cb-height" => Self::BadCbHeight,
+            "bad-cb-amount" => Self::BadCbAmount,
+            "bad-witness-nonce-size" => Self::BadWitnessNonceSize,
+            "bad-witness-merkle-match" => Self::BadWitnessMerkleMatch,
+            "bad-blk-length" => Self::BadBlkLength,
+            "bad-blk-weight" => Self::BadBlkWeight,
+            "bad-blk-sigops" => Self::BadBlkSigops,
+            _ => Self::Other(reason),
+        }
+    }
+}
+
+impl From<SubmitBlockResult> for Option<String> {
+    fn from(res: SubmitBlockResult) -> Self {
+        match res {
+            SubmitBlockResult::Other(reason) => Some(reason),
+            res => res.reason().map(Cow::into_owned),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SubmitBlockResult {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer).map(Self::from)
+    }
+}
+
+impl Serialize for SubmitBlockResult {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.reason().serialize(serializer)
+    }
+}
+
 #[rpc(client)]
 pub trait Main {
     #[method(name = "generate")]
@@ -473,6 +1750,14 @@ pub trait Main {
         block_template_request: BlockTemplateRequest,
     ) -> Result<BlockTemplate, jsonrpsee::core::Error>;
 
+    /// `getblocktemplate` in proposal mode.
+    /// See [`MainClientExt::propose_block_typed`] to check a block.
+    #[method(name = "getblocktemplate")]
+    async fn propose_block(
+        &self,
+        block_template_request: BlockTemplateRequest,
+    ) -> Result<SubmitBlockResult, jsonrpsee::core::Error>;
+
     #[method(name = "getblockchaininfo")]
     async fn get_blockchain_info(&self) -> Result<BlockchainInfo, jsonrpsee::core::Error>;
 
@@ -485,6 +1770,44 @@ pub trait Main {
         txid: Txid,
     ) -> Result<RawMempoolTxInfo, jsonrpsee::core::Error>;
 
+    #[method(name = "estimatesmartfee")]
+    async fn estimate_smart_fee(
+        &self,
+        conf_target: u16,
+        estimate_mode: Option<EstimateMode>,
+    ) -> Result<EstimateSmartFeeResult, jsonrpsee::core::Error>;
+
+    /// `threshold` is the proportion of transactions that must have
+    /// confirmed within the target for a fee rate to be considered
+    /// sufficient, between 0 and 1
+    #[method(name = "estimaterawfee")]
+    async fn estimate_raw_fee(
+        &self,
+        conf_target: u16,
+        threshold: Option<f64>,
+    ) -> Result<EstimateRawFeeResult, jsonrpsee::core::Error>;
+
+    #[method(name = "getmempoolinfo")]
+    async fn get_mempool_info(&self) -> Result<MempoolInfo, jsonrpsee::core::Error>;
+
+    // Max fee rate: BTC/kvB value
+    #[method(name = "testmempoolaccept")]
+    async fn test_mempool_accept(
+        &self,
+        raw_txs: Vec<String>,
+        max_fee_rate: Option<f64>,
+    ) -> Result<Vec<TestMempoolAcceptResult>, jsonrpsee::core::Error>;
+
+    // Max fee rate: BTC/kvB value
+    // Max burn amount: BTC value
+    #[method(name = "submitpackage")]
+    async fn submit_package(
+        &self,
+        package: Vec<String>,
+        max_fee_rate: Option<f64>,
+        max_burn_amount: Option<f64>,
+    ) -> Result<SubmitPackageResult, jsonrpsee::core::Error>;
+
     #[method(name = "getnetworkinfo")]
     async fn get_network_info(&self) -> jsonrpsee::core::RpcResult<NetworkInfo>;
 
@@ -519,6 +1842,187 @@ pub trait Main {
         address_type: &str,
     ) -> Result<bitcoin::Address<bitcoin::address::NetworkUnchecked>, jsonrpsee::core::Error>;
 
+    /// Create and load a wallet.
+    /// Passing `None` for optional arguments uses the node's defaults.
+    /// Params are sent by name, so that other optional arguments can be
+    /// omitted.
+    #[method(name = "createwallet", param_kind = map)]
+    async fn create_wallet(
+        &self,
+        wallet_name: &str,
+        disable_private_keys: Option<bool>,
+        blank: Option<bool>,
+        passphrase: Option<&str>,
+        descriptors: Option<bool>,
+        load_on_startup: Option<bool>,
+    ) -> Result<LoadWalletResult, jsonrpsee::core::Error>;
+
+    /// `filename` is the wallet name or path
+    #[method(name = "loadwallet")]
+    async fn load_wallet(
+        &self,
+        filename: &str,
+        load_on_startup: Option<bool>,
+    ) -> Result<LoadWalletResult, jsonrpsee::core::Error>;
+
+    /// If `wallet_name` is `None`, the wallet for the endpoint is unloaded
+    #[method(name = "unloadwallet")]
+    async fn unload_wallet(
+        &self,
+        wallet_name: Option<&str>,
+        load_on_startup: Option<bool>,
+    ) -> Result<UnloadWalletResult, jsonrpsee::core::Error>;
+
+    /// Names of the loaded wallets
+    #[method(name = "listwallets")]
+    async fn list_wallets(&self) -> Result<Vec<String>, jsonrpsee::core::Error>;
+
+    /// Wallets in the wallet directory, which may not be loaded
+    #[method(name = "listwalletdir")]
+    async fn list_wallet_dir(&self) -> Result<WalletDir, jsonrpsee::core::Error>;
+
+    /// Info for the wallet for the endpoint.
+    /// Fails with [`crate::BitcoinRpcErrorCode::RpcWalletNotSpecified`] if
+    /// more than one wallet is loaded, and the endpoint is not wallet-scoped.
+    #[method(name = "getwalletinfo")]
+    async fn get_wallet_info(&self) -> Result<WalletInfo, jsonrpsee::core::Error>;
+
+    /// Create a PSBT funded by the wallet
+    #[method(name = "walletcreatefundedpsbt")]
+    async fn wallet_create_funded_psbt(
+        &self,
+        inputs: Vec<CreateRawTxInput>,
+        outputs: Vec<CreateRawTxOutput>,
+        locktime: Option<u32>,
+        options: Option<WalletCreateFundedPsbtOptions>,
+        bip32derivs: Option<bool>,
+    ) -> Result<WalletCreateFundedPsbtResult, jsonrpsee::core::Error>;
+
+    /// Update the PSBT with wallet data, and sign inputs if `sign` is not
+    /// `false`.
+    /// `sighash_type` is eg. `ALL`, or `None` for the default.
+    #[method(name = "walletprocesspsbt")]
+    async fn wallet_process_psbt(
+        &self,
+        psbt: Base64Psbt,
+        sign: Option<bool>,
+        sighash_type: Option<&str>,
+        bip32derivs: Option<bool>,
+        finalize: Option<bool>,
+    ) -> Result<WalletProcessPsbtResult, jsonrpsee::core::Error>;
+
+    /// Finalize the inputs of the PSBT, and extract the transaction if
+    /// `extract` is not `false` and the PSBT is complete
+    #[method(name = "finalizepsbt")]
+    async fn finalize_psbt(
+        &self,
+        psbt: Base64Psbt,
+        extract: Option<bool>,
+    ) -> Result<FinalizePsbtResult, jsonrpsee::core::Error>;
+
+    /// Combine PSBTs for the same transaction
+    #[method(name = "combinepsbt")]
+    async fn combine_psbt(
+        &self,
+        txs: Vec<Base64Psbt>,
+    ) -> Result<Base64Psbt, jsonrpsee::core::Error>;
+
+    /// Join the inputs and outputs of PSBTs for distinct transactions
+    #[method(name = "joinpsbts")]
+    async fn join_psbts(&self, txs: Vec<Base64Psbt>) -> Result<Base64Psbt, jsonrpsee::core::Error>;
+
+    /// Add UTXO info from the UTXO set, mempool, and `descriptors`
+    #[method(name = "utxoupdatepsbt")]
+    async fn utxo_update_psbt(
+        &self,
+        psbt: Base64Psbt,
+        descriptors: Option<Vec<String>>,
+    ) -> Result<Base64Psbt, jsonrpsee::core::Error>;
+
+    /// Convert a raw transaction to a PSBT.
+    /// Fails if the transaction has signatures, unless `permit_sig_data` is
+    /// `true`, in which case signatures are discarded.
+    #[method(name = "converttopsbt")]
+    async fn convert_to_psbt(
+        &self,
+        tx_hex: String,
+        permit_sig_data: Option<bool>,
+        is_witness: Option<bool>,
+    ) -> Result<Base64Psbt, jsonrpsee::core::Error>;
+
+    #[method(name = "decodepsbt")]
+    async fn decode_psbt(
+        &self,
+        psbt: Base64Psbt,
+    ) -> Result<DecodePsbtResult, jsonrpsee::core::Error>;
+
+    #[method(name = "analyzepsbt")]
+    async fn analyze_psbt(
+        &self,
+        psbt: Base64Psbt,
+    ) -> Result<AnalyzePsbtResult, jsonrpsee::core::Error>;
+
+    #[method(name = "getdescriptorinfo")]
+    async fn get_descriptor_info(
+        &self,
+        descriptor: &str,
+    ) -> Result<DescriptorInfo, jsonrpsee::core::Error>;
+
+    /// `range` is required for ranged descriptors
+    #[method(name = "deriveaddresses")]
+    async fn derive_addresses(
+        &self,
+        descriptor: &str,
+        range: Option<DescriptorRange>,
+    ) -> Result<Vec<bitcoin::Address<bitcoin::address::NetworkUnchecked>>, jsonrpsee::core::Error>;
+
+    /// Import descriptors into the wallet, rescanning from the earliest
+    /// timestamp.
+    /// Results are in the same order as the requests.
+    #[method(name = "importdescriptors")]
+    async fn import_descriptors(
+        &self,
+        requests: Vec<ImportDescriptorRequest>,
+    ) -> Result<Vec<ImportDescriptorResult>, jsonrpsee::core::Error>;
+
+    /// If `private` is `true`, descriptors include private keys
+    #[method(name = "listdescriptors")]
+    async fn list_descriptors(
+        &self,
+        private: Option<bool>,
+    ) -> Result<ListDescriptorsResult, jsonrpsee::core::Error>;
+
+    /// Most recent wallet transaction entries, skipping the most recent
+    /// `skip` entries.
+    /// Returns entries for all labels if `label` is `None` or `*`.
+    #[method(name = "listtransactions")]
+    async fn list_transactions(
+        &self,
+        label: Option<&str>,
+        count: Option<usize>,
+        skip: Option<usize>,
+        include_watchonly: Option<bool>,
+    ) -> Result<Vec<ListTransactionsEntry>, jsonrpsee::core::Error>;
+
+    /// Wallet transaction entries in blocks since `block_hash`, or all
+    /// entries if `block_hash` is `None`
+    #[method(name = "listsinceblock")]
+    async fn list_since_block(
+        &self,
+        block_hash: Option<BlockHash>,
+        target_confirmations: Option<u32>,
+        include_watchonly: Option<bool>,
+        include_removed: Option<bool>,
+    ) -> Result<ListSinceBlockResult, jsonrpsee::core::Error>;
+
+    #[method(name = "gettransaction")]
+    async fn get_transaction(
+        &self,
+        txid: Txid,
+        include_watchonly: Option<bool>,
+        verbose: Option<bool>,
+    ) -> Result<GetTransactionResult, jsonrpsee::core::Error>;
+
     #[method(name = "getindexinfo")]
     async fn get_index_info(&self) -> Result<HashMap<String, IndexInfo>, jsonrpsee::core::Error>;
 
@@ -551,18 +2055,153 @@ pub trait Main {
     #[method(name = "stop")]
     async fn stop(&self) -> Result<String, jsonrpsee::core::Error>;
 
-    /// Returns None if the block is invalid, otherwise the error code describing why the
+    /// Returns None if the block was accepted, otherwise the error code describing why the
     /// block was rejected.
+    /// See [`MainClientExt::submit_block_typed`] for a typed result.
     #[method(name = "submitblock")]
     async fn submit_block(
         &self,
         block_hex: String,
     ) -> Result<Option<String>, jsonrpsee::core::Error>;
 
+    /// Returns an error if the header is invalid, or does not connect to a
+    /// known header
+    #[method(name = "submitheader")]
+    async fn submit_header(&self, header_hex: String) -> Result<(), jsonrpsee::core::Error>;
+
     #[method(name = "getzmqnotifications")]
     async fn get_zmq_notifications(&self) -> Result<Vec<ZMQNotification>, jsonrpsee::core::error>;
 }
 
+/// Variants of methods in [`MainClient`] that take and return consensus
+/// types
+pub trait MainClientExt: MainClient + Sync {
+    fn send_raw_transaction_typed(
+        &self,
+        tx: &bitcoin::Transaction,
+        max_fee_rate: Option<bitcoin::FeeRate>,
+        max_burn_amount: Option<bitcoin::Amount>,
+    ) -> impl Future<Output = Result<Txid, jsonrpsee::core::ClientError>> + Send {
+        let tx_hex = bitcoin::consensus::encode::serialize_hex(tx);
+        let max_fee_rate = max_fee_rate.map(|fee_rate| FeeRatePerKvb::from(fee_rate).0.to_btc());
+        let max_burn_amount = max_burn_amount.map(bitcoin::Amount::to_btc);
+        self.send_raw_transaction(tx_hex, max_fee_rate, max_burn_amount)
+    }
+
+    fn submit_block_typed(
+        &self,
+        block: &bitcoin::Block,
+    ) -> impl Future<Output = Result<SubmitBlockResult, jsonrpsee::core::ClientError>> + Send {
+        let block_hex = bitcoin::consensus::encode::serialize_hex(block);
+        async move {
+            self.submit_block(block_hex)
+                .await
+                .map(SubmitBlockResult::from)
+        }
+    }
+
+    /// Check a block without submitting it, using `getblocktemplate` in
+    /// proposal mode
+    fn propose_block_typed(
+        &self,
+        block: &bitcoin::Block,
+    ) -> impl Future<Output = Result<SubmitBlockResult, jsonrpsee::core::ClientError>> + Send {
+        self.propose_block(BlockTemplateRequest::proposal(block))
+    }
+
+    fn submit_header_typed(
+        &self,
+        header: &block::Header,
+    ) -> impl Future<Output = Result<(), jsonrpsee::core::ClientError>> + Send {
+        self.submit_header(bitcoin::consensus::encode::serialize_hex(header))
+    }
+
+    fn wallet_process_psbt_typed(
+        &self,
+        psbt: &bitcoin::Psbt,
+        sign: Option<bool>,
+        sighash_type: Option<&str>,
+        bip32derivs: Option<bool>,
+        finalize: Option<bool>,
+    ) -> impl Future<Output = Result<WalletProcessPsbtResult, jsonrpsee::core::ClientError>> + Send
+    {
+        self.wallet_process_psbt(
+            Base64Psbt(psbt.clone()),
+            sign,
+            sighash_type,
+            bip32derivs,
+            finalize,
+        )
+    }
+
+    fn finalize_psbt_typed(
+        &self,
+        psbt: &bitcoin::Psbt,
+        extract: Option<bool>,
+    ) -> impl Future<Output = Result<FinalizePsbtResult, jsonrpsee::core::ClientError>> + Send {
+        self.finalize_psbt(Base64Psbt(psbt.clone()), extract)
+    }
+
+    fn combine_psbt_typed(
+        &self,
+        psbts: &[bitcoin::Psbt],
+    ) -> impl Future<Output = Result<bitcoin::Psbt, jsonrpsee::core::ClientError>> + Send {
+        let psbts = psbts.iter().cloned().map(Base64Psbt).collect();
+        async move { self.combine_psbt(psbts).await.map(bitcoin::Psbt::from) }
+    }
+
+    fn join_psbts_typed(
+        &self,
+        psbts: &[bitcoin::Psbt],
+    ) -> impl Future<Output = Result<bitcoin::Psbt, jsonrpsee::core::ClientError>> + Send {
+        let psbts = psbts.iter().cloned().map(Base64Psbt).collect();
+        async move { self.join_psbts(psbts).await.map(bitcoin::Psbt::from) }
+    }
+
+    fn utxo_update_psbt_typed(
+        &self,
+        psbt: &bitcoin::Psbt,
+        descriptors: Option<Vec<String>>,
+    ) -> impl Future<Output = Result<bitcoin::Psbt, jsonrpsee::core::ClientError>> + Send {
+        let psbt = Base64Psbt(psbt.clone());
+        async move {
+            self.utxo_update_psbt(psbt, descriptors)
+                .await
+                .map(bitcoin::Psbt::from)
+        }
+    }
+
+    fn convert_to_psbt_typed(
+        &self,
+        tx: &bitcoin::Transaction,
+        permit_sig_data: Option<bool>,
+        is_witness: Option<bool>,
+    ) -> impl Future<Output = Result<bitcoin::Psbt, jsonrpsee::core::ClientError>> + Send {
+        let tx_hex = bitcoin::consensus::encode::serialize_hex(tx);
+        async move {
+            self.convert_to_psbt(tx_hex, permit_sig_data, is_witness)
+                .await
+                .map(bitcoin::Psbt::from)
+        }
+    }
+
+    fn decode_psbt_typed(
+        &self,
+        psbt: &bitcoin::Psbt,
+    ) -> impl Future<Output = Result<DecodePsbtResult, jsonrpsee::core::ClientError>> + Send {
+        self.decode_psbt(Base64Psbt(psbt.clone()))
+    }
+
+    fn analyze_psbt_typed(
+        &self,
+        psbt: &bitcoin::Psbt,
+    ) -> impl Future<Output = Result<AnalyzePsbtResult, jsonrpsee::core::ClientError>> + Send {
+        self.analyze_psbt(Base64Psbt(psbt.clone()))
+    }
+}
+
+impl<C> MainClientExt for C where C: MainClient + Sync {}
+
 pub struct U8Witness<const U8: u8>;
 
 impl<const U8: u8> Serialize for U8Witness<{ U8 }> {
@@ -610,6 +2249,18 @@ impl<'de> Deserialize<'de> for U8Witness<2> {
     }
 }
 
+impl<'de> Deserialize<'de> for U8Witness<3> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Debug, Deserialize)]
+        struct Repr(monostate::MustBe!(3));
+        let _ = Repr::deserialize(deserializer)?;
+        Ok(Self)
+    }
+}
+
 pub trait GetBlockVerbosity {
     type Response: DeserializeOwned;
 }
@@ -626,6 +2277,11 @@ impl GetBlockVerbosity for U8Witness<2> {
     type Response = Block<true>;
 }
 
+/// Transaction inputs include prevouts, if block undo data is available
+impl GetBlockVerbosity for U8Witness<3> {
+    type Response = Block<true>;
+}
+
 #[rpc(
     client,
     client_bounds(Verbosity: Serialize + Send + Sync + 'static)
@@ -642,9 +2298,172 @@ where
     ) -> Result<<Verbosity as GetBlockVerbosity>::Response, jsonrpsee::core::Error>;
 }
 
+/// Object to scan for with `scantxoutset`
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ScanObject {
+    Descriptor(String),
+    Ranged {
+        #[serde(rename = "desc")]
+        descriptor: String,
+        range: DescriptorRange,
+    },
+}
+
+/// UTXO found by `scantxoutset`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScannedUtxo {
+    pub txid: Txid,
+    pub vout: u32,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: bitcoin::ScriptBuf,
+    /// Inferred descriptor for the output
+    #[serde(rename = "desc")]
+    pub descriptor: String,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub amount: bitcoin::Amount,
+    /// `true` if the output was created by a coinbase transaction. Since
+    /// v25.
+    #[serde(default)]
+    pub coinbase: bool,
+    /// Height of the block that created the output
+    pub height: u32,
+    /// Hash of the block that created the output. Since v28.
+    #[serde(default, rename = "blockhash", skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<BlockHash>,
+}
+
+/// Result of `scantxoutset` with the `start` action
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScanTxOutSetResult {
+    /// `false` if the scan was aborted
+    pub success: bool,
+    /// Number of UTXOs scanned
+    #[serde(rename = "txouts")]
+    pub tx_outs: u64,
+    /// Height of the chain tip that the UTXO set was scanned at
+    pub height: u32,
+    #[serde(rename = "bestblock")]
+    pub best_block: BlockHash,
+    pub unspents: Vec<ScannedUtxo>,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub total_amount: bitcoin::Amount,
+}
+
+/// Result of `scantxoutset` with the `status` action
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct ScanProgress {
+    /// Percentage of the UTXO set scanned
+    pub progress: f64,
+}
+
+pub trait ScanTxOutSetAction {
+    type Response: DeserializeOwned;
+}
+
+/// Start a scan, and wait for it to complete
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ScanStart;
+
+/// Get the progress of the scan in progress
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ScanStatus;
+
+/// Abort the scan in progress
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ScanAbort;
+
+impl Serialize for ScanStart {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str("start")
+    }
+}
+
+impl Serialize for ScanStatus {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str("status")
+    }
+}
+
+impl Serialize for ScanAbort {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str("abort")
+    }
+}
+
+impl ScanTxOutSetAction for ScanStart {
+    type Response = ScanTxOutSetResult;
+}
+
+/// `None` if no scan is in progress
+impl ScanTxOutSetAction for ScanStatus {
+    type Response = Option<ScanProgress>;
+}
+
+/// `true` if a scan was aborted
+impl ScanTxOutSetAction for ScanAbort {
+    type Response = bool;
+}
+
+#[rpc(
+    client,
+    client_bounds(Action: Serialize + Send + Sync + 'static)
+)]
+pub trait ScanTxOutSet<Action>
+where
+    Action: ScanTxOutSetAction,
+{
+    /// `scan_objects` is required for [`ScanStart`], and ignored otherwise.
+    /// Only one scan can run at a time.
+    /// See [`crate::scan::TxOutSetScanner`] to poll the progress of a scan.
+    #[method(name = "scantxoutset")]
+    async fn scan_tx_out_set(
+        &self,
+        action: Action,
+        scan_objects: Option<Vec<ScanObject>>,
+    ) -> Result<<Action as ScanTxOutSetAction>::Response, jsonrpsee::core::Error>;
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct BoolWitness<const BOOL: bool>;
 
+pub trait GetBlockHeaderVerbosity {
+    type Response: DeserializeOwned;
+}
+
+impl GetBlockHeaderVerbosity for BoolWitness<false> {
+    type Response = ConsensusEncoded<bitcoin::block::Header>;
+}
+
+impl GetBlockHeaderVerbosity for BoolWitness<true> {
+    type Response = Header;
+}
+
+#[rpc(
+    client,
+    client_bounds(Verbose: Serialize + Send + Sync + 'static)
+)]
+pub trait GetBlockHeader<Verbose>
+where
+    Verbose: GetBlockHeaderVerbosity,
+{
+    #[method(name = "getblockheader")]
+    async fn get_block_header(
+        &self,
+        block_hash: BlockHash,
+        verbose: Verbose,
+    ) -> Result<<Verbose as GetBlockHeaderVerbosity>::Response, jsonrpsee::core::Error>;
+}
+
 impl<const BOOL: bool> Serialize for BoolWitness<{ BOOL }> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -719,6 +2538,43 @@ where
     >;
 }
 
+pub trait GetMempoolRelativesVerbosity {
+    type Response: DeserializeOwned;
+}
+
+impl GetMempoolRelativesVerbosity for BoolWitness<false> {
+    type Response = Vec<Txid>;
+}
+
+impl GetMempoolRelativesVerbosity for BoolWitness<true> {
+    type Response = RawMempoolVerbose;
+}
+
+#[rpc(
+    client,
+    client_bounds(Verbose: Serialize + Send + Sync + 'static)
+)]
+pub trait GetMempoolRelatives<Verbose>
+where
+    Verbose: GetMempoolRelativesVerbosity,
+{
+    /// In-mempool ancestors of the transaction
+    #[method(name = "getmempoolancestors")]
+    async fn get_mempool_ancestors(
+        &self,
+        txid: Txid,
+        verbose: Verbose,
+    ) -> Result<<Verbose as GetMempoolRelativesVerbosity>::Response, jsonrpsee::core::Error>;
+
+    /// In-mempool descendants of the transaction
+    #[method(name = "getmempooldescendants")]
+    async fn get_mempool_descendants(
+        &self,
+        txid: Txid,
+        verbose: Verbose,
+    ) -> Result<<Verbose as GetMempoolRelativesVerbosity>::Response, jsonrpsee::core::Error>;
+}
+
 pub trait GetRawTransactionVerbosity {
     type Response: DeserializeOwned;
 }
@@ -752,7 +2608,7 @@ impl<'de> Deserialize<'de> for GetRawTransactionVerbose<false> {
 }
 
 impl GetRawTransactionVerbosity for GetRawTransactionVerbose<true> {
-    type Response = serde_json::Value;
+    type Response = TxInfo;
 }
 
 impl<'de> Deserialize<'de> for GetRawTransactionVerbose<true> {
@@ -767,6 +2623,20 @@ impl<'de> Deserialize<'de> for GetRawTransactionVerbose<true> {
     }
 }
 
+impl GetRawTransactionVerbosity for U8Witness<0> {
+    type Response = String;
+}
+
+impl GetRawTransactionVerbosity for U8Witness<1> {
+    type Response = TxInfo;
+}
+
+/// Transaction inputs include prevouts, and the fee is included, if block
+/// undo data is available
+impl GetRawTransactionVerbosity for U8Witness<2> {
+    type Response = TxInfo;
+}
+
 #[rpc(client)]
 pub trait GetRawTransaction<T>
 where
@@ -0,0 +1,222 @@
+//! Cookie-file authentication.
+//!
+//! When bitcoind is started without `rpcuser`/`rpcpassword`, it writes a
+//! random `__cookie__:<password>` pair to `<datadir>/<network>/.cookie`, and
+//! regenerates it every time the node restarts.
+
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use jsonrpsee::{
+    core::{
+        client::{BatchResponse, ClientT},
+        params::BatchRequestBuilder,
+        traits::ToRpcParams,
+        ClientError,
+    },
+    http_client::{transport::Error as TransportError, HttpClient, HttpClientBuilder},
+};
+use serde::de::DeserializeOwned;
+
+use crate::{Error, RawParams};
+
+/// Default name of the cookie file
+pub const COOKIE_FILE_NAME: &str = ".cookie";
+
+/// Returns the network-specific data directory used by bitcoind,
+/// eg. `<datadir>/regtest` for regtest.
+pub fn network_datadir(datadir: &Path, network: bitcoin::Network) -> PathBuf {
+    match network {
+        bitcoin::Network::Bitcoin => datadir.to_owned(),
+        bitcoin::Network::Testnet => datadir.join("testnet3"),
+        bitcoin::Network::Testnet4 => datadir.join("testnet4"),
+        bitcoin::Network::Signet => datadir.join("signet"),
+        bitcoin::Network::Regtest => datadir.join("regtest"),
+    }
+}
+
+/// Returns the default cookie file path for the specified datadir and network
+pub fn default_cookie_path(datadir: &Path, network: bitcoin::Network) -> PathBuf {
+    network_datadir(datadir, network).join(COOKIE_FILE_NAME)
+}
+
+/// Credentials read from a cookie file
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cookie {
+    pub user: String,
+    pub password: String,
+}
+
+impl Cookie {
+    /// Read credentials from a cookie file
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let contents = std::fs::read_to_string(path).map_err(|source| Error::ReadCookieFile {
+            source,
+            path: path.to_owned(),
+        })?;
+        contents.parse().map_err(|()| Error::InvalidCookieFile {
+            path: path.to_owned(),
+        })
+    }
+}
+
+impl std::str::FromStr for Cookie {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (user, password) = s.trim_end().split_once(':').ok_or(())?;
+        Ok(Self {
+            user: user.to_owned(),
+            password: password.to_owned(),
+        })
+    }
+}
+
+/// Returns `true` if the server rejected the request with HTTP 401
+fn is_unauthorized(err: &ClientError) -> bool {
+    match err {
+        ClientError::Transport(err) => matches!(
+            err.downcast_ref::<TransportError>(),
+            Some(TransportError::Rejected { status_code: 401 })
+        ),
+        _ => false,
+    }
+}
+
+/// Client that authenticates using a cookie file.
+/// If the server responds with HTTP 401, (eg. because bitcoind restarted and
+/// regenerated the cookie), the cookie file is re-read and the request is
+/// retried once.
+#[derive(Clone, Debug)]
+pub struct CookieClient {
+    builder: HttpClientBuilder,
+    cookie_path: PathBuf,
+    inner: Arc<RwLock<HttpClient>>,
+    url: url::Url,
+}
+
+impl CookieClient {
+    /// Use the `builder` argument to manually set client options
+    /// `url` MUST NOT contain credentials.
+    pub fn new(
+        url: url::Url,
+        builder: Option<HttpClientBuilder>,
+        cookie_path: PathBuf,
+    ) -> Result<Self, Error> {
+        let builder = builder.unwrap_or_default();
+        let inner = Self::build_inner(&url, &builder, &cookie_path)?;
+        Ok(Self {
+            builder,
+            cookie_path,
+            inner: Arc::new(RwLock::new(inner)),
+            url,
+        })
+    }
+
+    fn build_inner(
+        url: &url::Url,
+        builder: &HttpClientBuilder,
+        cookie_path: &Path,
+    ) -> Result<HttpClient, Error> {
+        let cookie = Cookie::read(cookie_path)?;
+        crate::build_client(
+            url.as_str(),
+            Some(builder.clone()),
+            Some((&cookie.user, &cookie.password)),
+        )
+    }
+
+    /// Client for the wallet endpoint `/wallet/<name>`, with the same host,
+    /// options and cookie file
+    pub fn wallet(&self, name: &str) -> Result<Self, Error> {
+        Self::new(
+            crate::config::wallet_url(&self.url, name),
+            Some(self.builder.clone()),
+            self.cookie_path.clone(),
+        )
+    }
+
+    pub fn cookie_path(&self) -> &Path {
+        &self.cookie_path
+    }
+
+    /// Re-read the cookie file, and use the new credentials for subsequent
+    /// requests
+    pub fn refresh(&self) -> Result<(), Error> {
+        let inner = Self::build_inner(&self.url, &self.builder, &self.cookie_path)?;
+        *self.inner.write().unwrap_or_else(PoisonError::into_inner) = inner;
+        Ok(())
+    }
+
+    fn current(&self) -> HttpClient {
+        self.inner
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Run `f`, refreshing the cookie and retrying once if the request was
+    /// rejected as unauthorized
+    async fn with_refresh<F, Fut, T>(&self, f: F) -> Result<T, ClientError>
+    where
+        F: Fn(HttpClient) -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        match f(self.current()).await {
+            Err(err) if is_unauthorized(&err) => (),
+            res => return res,
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            cookie_path = %self.cookie_path.display(),
+            "request unauthorized, re-reading cookie file"
+        );
+        self.refresh()
+            .map_err(|err| ClientError::Transport(Box::new(err)))?;
+        f(self.current()).await
+    }
+}
+
+impl ClientT for CookieClient {
+    async fn notification<Params>(&self, method: &str, params: Params) -> Result<(), ClientError>
+    where
+        Params: ToRpcParams + Send,
+    {
+        let params = RawParams(params.to_rpc_params()?);
+        self.with_refresh(|client| {
+            let params = params.clone();
+            async move { client.notification(method, params).await }
+        })
+        .await
+    }
+
+    async fn request<R, Params>(&self, method: &str, params: Params) -> Result<R, ClientError>
+    where
+        R: DeserializeOwned,
+        Params: ToRpcParams + Send,
+    {
+        let params = RawParams(params.to_rpc_params()?);
+        self.with_refresh(|client| {
+            let params = params.clone();
+            async move { client.request(method, params).await }
+        })
+        .await
+    }
+
+    async fn batch_request<'a, R>(
+        &self,
+        batch: BatchRequestBuilder<'a>,
+    ) -> Result<BatchResponse<'a, R>, ClientError>
+    where
+        R: DeserializeOwned + std::fmt::Debug + 'a,
+    {
+        self.with_refresh(|client| {
+            let batch = batch.clone();
+            async move { client.batch_request(batch).await }
+        })
+        .await
+    }
+}
@@ -0,0 +1,355 @@
+//! Fee rate estimates derived from the verbose mempool.
+//!
+//! [`MempoolFeeEstimator`] projects the next blocks by simulating the
+//! ancestor fee rate block assembly used by Bitcoin Core, so that fee rates
+//! can be estimated from the current mempool alone, eg. when
+//! `estimatesmartfee` has insufficient data.
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use bitcoin::{Amount, FeeRate, Weight};
+
+use crate::client::RawMempoolVerbose;
+
+/// Default maximum block weight used by Bitcoin Core for block templates,
+/// leaving space for the coinbase transaction
+pub const DEFAULT_BLOCK_MAX_WEIGHT: Weight = Weight::from_wu(3_996_000);
+
+/// Number of consecutive packages that can fail to fit in a nearly full
+/// block before the block is considered full, as in Bitcoin Core
+const MAX_CONSECUTIVE_FAILURES: usize = 1000;
+
+/// A block is nearly full if the remaining weight is less than this
+const BLOCK_FULL_MARGIN: Weight = Weight::from_wu(4000);
+
+/// Lower bounds of the fee rate bands used by mempool.space, in sat/vB
+pub const DEFAULT_HISTOGRAM_BANDS_SAT_PER_VB: &[u64] = &[
+    1, 2, 3, 4, 5, 6, 8, 10, 12, 15, 20, 30, 40, 50, 60, 70, 80, 90, 100, 125, 150, 175, 200, 250,
+    300, 350, 400, 500, 600, 700, 800, 900, 1000, 1200, 1400, 1600, 1800, 2000,
+];
+
+/// Fee rate for a fee and weight, dividing with `div`
+fn fee_rate_with<F>(fee: u64, weight: u64, div: F) -> FeeRate
+where
+    F: FnOnce(u128, u128) -> u128,
+{
+    match weight {
+        0 => FeeRate::ZERO,
+        weight => FeeRate::from_sat_per_kwu(
+            div(u128::from(fee) * 1000, u128::from(weight))
+                .try_into()
+                .unwrap_or(u64::MAX),
+        ),
+    }
+}
+
+/// Fee rate for a fee and weight, rounded down
+fn fee_rate(fee: u64, weight: u64) -> FeeRate {
+    fee_rate_with(fee, weight, |fee, weight| fee / weight)
+}
+
+/// Fee rate for a fee and weight, rounded up
+fn fee_rate_ceil(fee: u64, weight: u64) -> FeeRate {
+    fee_rate_with(fee, weight, u128::div_ceil)
+}
+
+#[derive(Debug)]
+struct Node {
+    /// Modified fee in sats
+    fee: u64,
+    weight: u64,
+    vsize: u64,
+    parents: Vec<usize>,
+    /// Derived from `parents` of other nodes
+    children: Vec<usize>,
+    /// Modified fee of the transaction and ancestors that are not yet
+    /// included in a block
+    ancestor_fee: u64,
+    /// Weight of the transaction and ancestors that are not yet included
+    /// in a block
+    ancestor_weight: u64,
+    included: bool,
+}
+
+/// Ancestor package that can be included in a block
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Candidate {
+    fee: u64,
+    weight: u64,
+    idx: usize,
+}
+
+impl Ord for Candidate {
+    /// Ordered by fee rate, and then by index for determinism
+    fn cmp(&self, other: &Self) -> Ordering {
+        let lhs = u128::from(self.fee) * u128::from(other.weight);
+        let rhs = u128::from(other.fee) * u128::from(self.weight);
+        lhs.cmp(&rhs).then_with(|| other.idx.cmp(&self.idx))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Walk the graph from `idx`, not including `idx` or transactions that are
+/// already included in a block
+fn walk<F>(nodes: &[Node], idx: usize, next: F) -> Vec<usize>
+where
+    F: Fn(&Node) -> &[usize],
+{
+    let mut visited = HashSet::new();
+    let mut stack = next(&nodes[idx]).to_vec();
+    let mut res = Vec::new();
+    while let Some(idx) = stack.pop() {
+        if nodes[idx].included || !visited.insert(idx) {
+            continue;
+        }
+        res.push(idx);
+        stack.extend_from_slice(next(&nodes[idx]));
+    }
+    res
+}
+
+/// Block projected from the mempool
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ProjectedBlock {
+    pub weight: Weight,
+    /// Total modified fees
+    pub fees: Amount,
+    pub tx_count: usize,
+    /// Lowest ancestor package fee rate included in the block, rounded up
+    /// to the nearest sat/kwu
+    pub min_fee_rate: FeeRate,
+    /// Highest ancestor package fee rate included in the block, rounded
+    /// down to the nearest sat/kwu
+    pub max_fee_rate: FeeRate,
+}
+
+impl ProjectedBlock {
+    fn new() -> Self {
+        Self {
+            weight: Weight::ZERO,
+            fees: Amount::ZERO,
+            tx_count: 0,
+            min_fee_rate: FeeRate::MAX,
+            max_fee_rate: FeeRate::ZERO,
+        }
+    }
+}
+
+/// Range of fee rates in a fee rate histogram
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FeeRateBand {
+    /// Lowest fee rate in the band
+    pub min_fee_rate: FeeRate,
+    /// Total virtual size of transactions in the band
+    pub vsize: u64,
+    pub tx_count: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct MempoolFeeEstimator {
+    blocks: Vec<ProjectedBlock>,
+    /// Effective fee rate and vsize for each transaction. The effective fee
+    /// rate is the fee rate of the package with which the transaction was
+    /// included in a projected block.
+    txs: Vec<(FeeRate, u64)>,
+}
+
+impl MempoolFeeEstimator {
+    /// Project blocks of up to `weight_limit` until the mempool is empty.
+    /// Modified fees are used, as in block assembly.
+    /// Use [`DEFAULT_BLOCK_MAX_WEIGHT`] for the default block weight limit.
+    pub fn new(mempool: &RawMempoolVerbose, weight_limit: Weight) -> Self {
+        let indices: HashMap<_, _> = mempool
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(idx, (txid, _))| (*txid, idx))
+            .collect();
+        let mut nodes: Vec<Node> = mempool
+            .entries
+            .iter()
+            .map(|(_, entry)| Node {
+                fee: entry.fees.modified.to_sat(),
+                weight: entry.weight,
+                vsize: entry.vsize,
+                parents: entry
+                    .depends
+                    .iter()
+                    .filter_map(|txid| indices.get(txid).copied())
+                    .collect(),
+                children: Vec::new(),
+                ancestor_fee: 0,
+                ancestor_weight: 0,
+                included: false,
+            })
+            .collect();
+        // Children are derived from parents rather than read from `spentby`,
+        // so that descendants are always consistent with ancestors, even if
+        // the two lists are out of step in the snapshot
+        for idx in 0..nodes.len() {
+            for parent in nodes[idx].parents.clone() {
+                nodes[parent].children.push(idx);
+            }
+        }
+        for idx in 0..nodes.len() {
+            let ancestors = walk(&nodes, idx, |node| &node.parents);
+            let node = &nodes[idx];
+            let (fee, weight) =
+                ancestors
+                    .into_iter()
+                    .fold((node.fee, node.weight), |(fee, weight), ancestor| {
+                        (fee + nodes[ancestor].fee, weight + nodes[ancestor].weight)
+                    });
+            nodes[idx].ancestor_fee = fee;
+            nodes[idx].ancestor_weight = weight;
+        }
+        let mut heap: BinaryHeap<Candidate> = nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| Candidate {
+                fee: node.ancestor_fee,
+                weight: node.ancestor_weight,
+                idx,
+            })
+            .collect();
+        let mut effective_fee_rates = vec![None; nodes.len()];
+        let mut blocks = Vec::new();
+        let mut block = ProjectedBlock::new();
+        let mut deferred = Vec::new();
+        let mut consecutive_failures = 0;
+        let mut remaining = nodes.len();
+        while remaining > 0 {
+            let block_full = consecutive_failures > MAX_CONSECUTIVE_FAILURES
+                && block.weight + BLOCK_FULL_MARGIN > weight_limit;
+            let candidate = if block_full { None } else { heap.pop() };
+            let Some(candidate) = candidate else {
+                if block.tx_count == 0 {
+                    // Remaining packages do not fit in any block
+                    break;
+                }
+                blocks.push(block);
+                block = ProjectedBlock::new();
+                heap.extend(deferred.drain(..));
+                consecutive_failures = 0;
+                continue;
+            };
+            let node = &nodes[candidate.idx];
+            if node.included
+                || (node.ancestor_fee, node.ancestor_weight) != (candidate.fee, candidate.weight)
+            {
+                continue;
+            }
+            if block.weight + Weight::from_wu(candidate.weight) > weight_limit {
+                deferred.push(candidate);
+                consecutive_failures += 1;
+                continue;
+            }
+            consecutive_failures = 0;
+            let package_fee_rate = fee_rate(candidate.fee, candidate.weight);
+            let mut package = walk(&nodes, candidate.idx, |node| &node.parents);
+            package.push(candidate.idx);
+            for &idx in &package {
+                nodes[idx].included = true;
+                effective_fee_rates[idx] = Some(package_fee_rate);
+            }
+            remaining -= package.len();
+            block.weight += Weight::from_wu(candidate.weight);
+            block.fees += Amount::from_sat(candidate.fee);
+            block.tx_count += package.len();
+            // Rounded up, so that a transaction paying the minimum fee rate
+            // is not below the lowest package fee rate in the block
+            block.min_fee_rate = block
+                .min_fee_rate
+                .min(fee_rate_ceil(candidate.fee, candidate.weight));
+            block.max_fee_rate = block.max_fee_rate.max(package_fee_rate);
+            // Remove the package from the ancestor fees and weights of
+            // descendants
+            let mut updated = HashSet::new();
+            for &idx in &package {
+                let (fee, weight) = (nodes[idx].fee, nodes[idx].weight);
+                for descendant in walk(&nodes, idx, |node| &node.children) {
+                    let descendant_node = &mut nodes[descendant];
+                    descendant_node.ancestor_fee -= fee;
+                    descendant_node.ancestor_weight -= weight;
+                    updated.insert(descendant);
+                }
+            }
+            heap.extend(updated.into_iter().map(|idx| Candidate {
+                fee: nodes[idx].ancestor_fee,
+                weight: nodes[idx].ancestor_weight,
+                idx,
+            }));
+        }
+        if block.tx_count != 0 {
+            blocks.push(block);
+        }
+        let txs = nodes
+            .iter()
+            .zip(effective_fee_rates)
+            .filter_map(|(node, fee_rate)| Some((fee_rate?, node.vsize)))
+            .collect();
+        Self { blocks, txs }
+    }
+
+    /// Projected blocks, in order
+    pub fn projected_blocks(&self) -> &[ProjectedBlock] {
+        &self.blocks
+    }
+
+    /// Estimated fee rate for a transaction to confirm within `target`
+    /// blocks, assuming no new transactions.
+    /// Returns `floor` if the mempool is projected to clear within
+    /// `target` blocks, eg. the `mempoolminfee` from `getmempoolinfo`.
+    /// The estimate is rounded up, so that it is not below the lowest
+    /// package fee rate in the projected block.
+    pub fn fee_rate_for_target(&self, target: usize, floor: FeeRate) -> FeeRate {
+        let target = target.max(1);
+        if self.blocks.len() <= target {
+            return floor;
+        }
+        self.blocks[target - 1].min_fee_rate.max(floor)
+    }
+
+    /// Histogram of effective fee rates, with bands starting at each of
+    /// `band_floors`, which must be in ascending order.
+    /// Transactions with a fee rate below the first band are included in the
+    /// first band.
+    pub fn histogram(&self, band_floors: &[FeeRate]) -> Vec<FeeRateBand> {
+        let mut res: Vec<FeeRateBand> = band_floors
+            .iter()
+            .map(|min_fee_rate| FeeRateBand {
+                min_fee_rate: *min_fee_rate,
+                vsize: 0,
+                tx_count: 0,
+            })
+            .collect();
+        if res.is_empty() {
+            return res;
+        }
+        for (fee_rate, vsize) in &self.txs {
+            let idx = band_floors
+                .partition_point(|min_fee_rate| min_fee_rate <= fee_rate)
+                .saturating_sub(1);
+            res[idx].vsize += vsize;
+            res[idx].tx_count += 1;
+        }
+        res
+    }
+
+    /// Histogram using [`DEFAULT_HISTOGRAM_BANDS_SAT_PER_VB`]
+    pub fn default_histogram(&self) -> Vec<FeeRateBand> {
+        let band_floors: Vec<FeeRate> = DEFAULT_HISTOGRAM_BANDS_SAT_PER_VB
+            .iter()
+            .map(|sat_per_vb| FeeRate::from_sat_per_kwu(sat_per_vb * 250))
+            .collect();
+        self.histogram(&band_floors)
+    }
+}
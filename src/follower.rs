@@ -0,0 +1,146 @@
+//! Chain tip follower.
+//!
+//! [`ChainFollower`] polls the node for changes to the best chain, and emits
+//! an event for each block that is connected or disconnected, so that
+//! callers can update their own view of the chain one block at a time.
+
+use std::time::Duration;
+
+use bitcoin::BlockHash;
+use futures::Stream;
+
+use crate::{client::MainClient, BitcoinRpcErrorCode, Error, Header, RpcErrorExt as _};
+
+/// Change to the best chain
+#[derive(Clone, Debug)]
+pub enum ChainEvent {
+    /// The block was connected to the tip of the best chain
+    BlockConnected(Header),
+    /// The block was disconnected from the tip of the best chain, during a
+    /// reorg. The new tip is [`Header::prev_blockhash`].
+    BlockDisconnected(Header),
+}
+
+impl ChainEvent {
+    pub fn header(&self) -> &Header {
+        match self {
+            Self::BlockConnected(header) | Self::BlockDisconnected(header) => header,
+        }
+    }
+
+    /// The tip of the followed chain after this event
+    pub fn tip(&self) -> BlockHash {
+        match self {
+            Self::BlockConnected(header) => header.hash,
+            Self::BlockDisconnected(header) => header.prev_blockhash,
+        }
+    }
+}
+
+/// Follows the best chain, starting from a known block.
+/// On reorgs, blocks are disconnected back to the fork point with the best
+/// chain, and then the blocks in the best chain are connected.
+/// To resume after a restart, create a follower starting from
+/// [`ChainEvent::tip`] for the last processed event.
+#[derive(Clone, Debug)]
+pub struct ChainFollower<C> {
+    client: C,
+    tip: BlockHash,
+    poll_interval: Duration,
+}
+
+impl<C> ChainFollower<C>
+where
+    C: MainClient + Sync,
+{
+    pub fn new(client: C, tip: BlockHash, poll_interval: Duration) -> Self {
+        Self {
+            client,
+            tip,
+            poll_interval,
+        }
+    }
+
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// The last block that was connected, or the parent of the last block
+    /// that was disconnected
+    pub fn tip(&self) -> BlockHash {
+        self.tip
+    }
+
+    async fn get_header(&self, block_hash: BlockHash) -> Result<Header, Error> {
+        self.client
+            .getblockheader(block_hash)
+            .await
+            .map_err(|source| Error::Request {
+                source,
+                method: "getblockheader",
+            })
+    }
+
+    /// Get the header for the tip.
+    /// Returns [`Error::NoNextBlock`] if the tip is unknown to the node.
+    async fn get_tip_header(&self) -> Result<Header, Error> {
+        match self.get_header(self.tip).await {
+            Err(Error::Request { source, .. })
+                if source.rpc_code() == Some(BitcoinRpcErrorCode::RpcInvalidAddressOrKey) =>
+            {
+                Err(Error::NoNextBlock {
+                    prev_main_hash: self.tip,
+                })
+            }
+            res => res,
+        }
+    }
+
+    /// Wait until the best block differs from the tip
+    async fn wait_for_new_block(&self) -> Result<(), Error> {
+        loop {
+            let best_block_hash =
+                self.client
+                    .getbestblockhash()
+                    .await
+                    .map_err(|source| Error::Request {
+                        source,
+                        method: "getbestblockhash",
+                    })?;
+            if best_block_hash != self.tip {
+                return Ok(());
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Wait for the next change to the best chain.
+    /// Returns [`Error::NoNextBlock`] if the tip is unknown to the node.
+    pub async fn next_event(&mut self) -> Result<ChainEvent, Error> {
+        loop {
+            let header = self.get_tip_header().await?;
+            // Blocks that are not in the best chain have -1 confirmations
+            if header.confirmations < 0 {
+                self.tip = header.prev_blockhash;
+                return Ok(ChainEvent::BlockDisconnected(header));
+            }
+            match header.next_blockhash {
+                Some(next_blockhash) => {
+                    let next_header = self.get_header(next_blockhash).await?;
+                    self.tip = next_header.hash;
+                    return Ok(ChainEvent::BlockConnected(next_header));
+                }
+                None => self.wait_for_new_block().await?,
+            }
+        }
+    }
+
+    /// Stream of changes to the best chain.
+    /// The stream ends after the first error.
+    pub fn into_stream(self) -> impl Stream<Item = Result<ChainEvent, Error>> {
+        futures::stream::try_unfold(self, |mut follower| async move {
+            let event = follower.next_event().await?;
+            Ok(Some((event, follower)))
+        })
+    }
+}
@@ -0,0 +1,93 @@
+//! Block template long polling.
+//!
+//! [`BlockTemplatePoller`] re-issues `getblocktemplate` requests with the
+//! long poll ID of the previous template, as described in BIP22, so that a
+//! new template is received as soon as the previous template is stale.
+
+use std::time::Duration;
+
+use futures::Stream;
+use jsonrpsee::core::ClientError;
+
+use crate::{
+    client::{BlockTemplate, BlockTemplateRequest, MainClient},
+    Error,
+};
+
+/// Long polls for block templates.
+/// Each long poll request is abandoned and re-issued after a timeout, so
+/// that requests are not dropped by proxies or the client's own request
+/// timeout. The timeout should be less than the client request timeout.
+#[derive(Clone, Debug)]
+pub struct BlockTemplatePoller<C> {
+    client: C,
+    request: BlockTemplateRequest,
+    timeout: Duration,
+    /// Long poll ID of the last template, if any
+    long_poll_id: Option<String>,
+    /// `true` if a template has already been received
+    started: bool,
+}
+
+impl<C> BlockTemplatePoller<C>
+where
+    C: MainClient + Sync,
+{
+    /// Any long poll ID in `request` is replaced with the long poll ID of
+    /// the last template
+    pub fn new(client: C, request: BlockTemplateRequest, timeout: Duration) -> Self {
+        Self {
+            client,
+            request,
+            timeout,
+            long_poll_id: None,
+            started: false,
+        }
+    }
+
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Get the next block template.
+    /// The first template is returned immediately.
+    /// If the node does not support long polling, templates are requested
+    /// once per timeout.
+    pub async fn next_template(&mut self) -> Result<BlockTemplate, Error> {
+        loop {
+            if self.started && self.long_poll_id.is_none() {
+                tokio::time::sleep(self.timeout).await;
+            }
+            let request = BlockTemplateRequest {
+                long_poll_id: self.long_poll_id.clone(),
+                ..self.request.clone()
+            };
+            let res =
+                tokio::time::timeout(self.timeout, self.client.get_block_template(request)).await;
+            match res {
+                Ok(Ok(template)) => {
+                    self.long_poll_id = template.long_poll_id.clone();
+                    self.started = true;
+                    return Ok(template);
+                }
+                // Re-issue the request
+                Err(_) | Ok(Err(ClientError::RequestTimeout)) => continue,
+                Ok(Err(source)) => {
+                    return Err(Error::Request {
+                        source,
+                        method: "getblocktemplate",
+                    })
+                }
+            }
+        }
+    }
+
+    /// Stream of block templates.
+    /// The stream ends after the first error.
+    pub fn into_stream(self) -> impl Stream<Item = Result<BlockTemplate, Error>> {
+        futures::stream::try_unfold(self, |mut poller| async move {
+            let template = poller.next_template().await?;
+            Ok(Some((template, poller)))
+        })
+    }
+}
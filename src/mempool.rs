@@ -0,0 +1,340 @@
+//! Local mirror of the node's mempool.
+//!
+//! [`MempoolMirror`] is bootstrapped from `getrawmempool` with
+//! `mempool_sequence = true`, and then kept in sync with incremental updates,
+//! eg. from the ZMQ `sequence` topic. The mempool sequence number increases
+//! by one for each transaction that is added to or removed from the mempool,
+//! so that missed updates can be detected, in which case the mirror is
+//! resynced.
+
+use std::collections::{HashMap, HashSet};
+
+use bitcoin::{Txid, Wtxid};
+use jsonrpsee::core::{client::ClientT, ClientError};
+
+use crate::{
+    batch::{BatchEntryError, BatchRequest},
+    client::{BoolWitness, GetRawMempoolClient as _, RawMempoolTxInfo},
+    BitcoinRpcErrorCode, Error,
+};
+
+/// Maximum number of entries requested in a single batch
+const ENTRIES_BATCH_SIZE: usize = 1000;
+
+/// Outcome of applying an update to a [`MempoolMirror`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MempoolUpdate {
+    /// The update was applied
+    Applied,
+    /// The update was older than the mirror, and was ignored
+    Stale,
+    /// Updates were missed, and the mirror was resynced
+    Resynced,
+}
+
+/// Mirror of the node's mempool.
+/// The fees, sizes and counts in each [`RawMempoolTxInfo`] are as of when
+/// the entry was fetched. The `depends` and `spent_by` fields are kept up to
+/// date as transactions are added and removed.
+#[derive(Clone, Debug, Default)]
+pub struct MempoolMirror {
+    /// Sequence number of the next expected update
+    mempool_sequence: u64,
+    entries: HashMap<Txid, RawMempoolTxInfo>,
+    txids_by_wtxid: HashMap<Wtxid, Txid>,
+}
+
+impl MempoolMirror {
+    /// Create a mirror from the node's current mempool
+    pub async fn new<C>(client: &C) -> Result<Self, Error>
+    where
+        C: ClientT + Sync,
+    {
+        let mut res = Self::default();
+        res.resync(client).await?;
+        Ok(res)
+    }
+
+    /// Sequence number of the next expected update. This is the
+    /// `mempool_sequence` returned by `getrawmempool` when the mirror was
+    /// last synced, plus the number of updates applied since.
+    pub fn mempool_sequence(&self) -> u64 {
+        self.mempool_sequence
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, txid: &Txid) -> bool {
+        self.entries.contains_key(txid)
+    }
+
+    pub fn get(&self, txid: &Txid) -> Option<&RawMempoolTxInfo> {
+        self.entries.get(txid)
+    }
+
+    pub fn get_by_wtxid(&self, wtxid: &Wtxid) -> Option<(Txid, &RawMempoolTxInfo)> {
+        let txid = self.txids_by_wtxid.get(wtxid)?;
+        self.entries.get(txid).map(|entry| (*txid, entry))
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&Txid, &RawMempoolTxInfo)> {
+        self.entries.iter()
+    }
+
+    /// Unconfirmed parents of the transaction
+    pub fn parents(&self, txid: &Txid) -> &[Txid] {
+        self.entries
+            .get(txid)
+            .map(|entry| entry.depends.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Transactions in the mempool that spend outputs of the transaction
+    pub fn children(&self, txid: &Txid) -> &[Txid] {
+        self.entries
+            .get(txid)
+            .map(|entry| entry.spent_by.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Walk the graph from `txid`, not including `txid`
+    fn walk<'a, F>(&'a self, txid: &Txid, next: F) -> HashSet<Txid>
+    where
+        F: Fn(&'a Self, &Txid) -> &'a [Txid],
+    {
+        let mut res = HashSet::new();
+        let mut stack = next(self, txid).to_vec();
+        while let Some(txid) = stack.pop() {
+            if res.insert(txid) {
+                stack.extend_from_slice(next(self, &txid));
+            }
+        }
+        res
+    }
+
+    /// All in-mempool ancestors of the transaction
+    pub fn ancestors(&self, txid: &Txid) -> HashSet<Txid> {
+        self.walk(txid, Self::parents)
+    }
+
+    /// All in-mempool descendants of the transaction
+    pub fn descendants(&self, txid: &Txid) -> HashSet<Txid> {
+        self.walk(txid, Self::children)
+    }
+
+    fn insert(&mut self, txid: Txid, entry: RawMempoolTxInfo) {
+        self.remove(&txid);
+        for parent in &entry.depends {
+            if let Some(parent) = self.entries.get_mut(parent) {
+                if !parent.spent_by.contains(&txid) {
+                    parent.spent_by.push(txid);
+                }
+            }
+        }
+        for child in &entry.spent_by {
+            if let Some(child) = self.entries.get_mut(child) {
+                if !child.depends.contains(&txid) {
+                    child.depends.push(txid);
+                }
+            }
+        }
+        self.txids_by_wtxid.insert(entry.wtxid, txid);
+        self.entries.insert(txid, entry);
+    }
+
+    fn remove(&mut self, txid: &Txid) -> Option<RawMempoolTxInfo> {
+        let entry = self.entries.remove(txid)?;
+        for parent in &entry.depends {
+            if let Some(parent) = self.entries.get_mut(parent) {
+                parent.spent_by.retain(|spender| spender != txid);
+            }
+        }
+        for child in &entry.spent_by {
+            if let Some(child) = self.entries.get_mut(child) {
+                child.depends.retain(|parent| parent != txid);
+            }
+        }
+        self.txids_by_wtxid.remove(&entry.wtxid);
+        Some(entry)
+    }
+
+    /// Fetch entries with `getmempoolentry`, skipping transactions that are
+    /// no longer in the mempool
+    async fn fetch_entries<C>(
+        client: &C,
+        txids: &[Txid],
+    ) -> Result<Vec<(Txid, RawMempoolTxInfo)>, Error>
+    where
+        C: ClientT + Sync,
+    {
+        let request_err = |source| Error::Request {
+            source,
+            method: "getmempoolentry",
+        };
+        let mut res = Vec::with_capacity(txids.len());
+        for txids in txids.chunks(ENTRIES_BATCH_SIZE) {
+            let mut batch = BatchRequest::new();
+            let handles = txids
+                .iter()
+                .map(|txid| batch.get_mempool_entry(*txid))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| request_err(ClientError::ParseError(err)))?;
+            let responses = batch.send(client).await.map_err(request_err)?;
+            for (txid, handle) in txids.iter().zip(handles) {
+                match responses.get(handle) {
+                    Ok(entry) => res.push((*txid, entry)),
+                    Err(BatchEntryError::Rpc(err))
+                        if err.code() == BitcoinRpcErrorCode::RpcInvalidAddressOrKey.code() => {}
+                    Err(BatchEntryError::Rpc(err)) => {
+                        return Err(request_err(ClientError::Call(err)))
+                    }
+                    Err(BatchEntryError::Deserialize(err)) => {
+                        return Err(request_err(ClientError::ParseError(err)))
+                    }
+                    Err(err @ BatchEntryError::Missing { .. }) => {
+                        return Err(request_err(ClientError::Custom(err.to_string())))
+                    }
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    /// Resync with the node's mempool.
+    /// Only entries for transactions that are not already in the mirror are
+    /// fetched.
+    pub async fn resync<C>(&mut self, client: &C) -> Result<(), Error>
+    where
+        C: ClientT + Sync,
+    {
+        let mempool = client
+            .get_raw_mempool(BoolWitness::<false>, BoolWitness::<true>)
+            .await
+            .map_err(|source| Error::Request {
+                source,
+                method: "getrawmempool",
+            })?;
+        let txids: HashSet<Txid> = mempool.txids.into_iter().collect();
+        let removed: Vec<Txid> = self
+            .entries
+            .keys()
+            .filter(|txid| !txids.contains(*txid))
+            .copied()
+            .collect();
+        for txid in removed {
+            self.remove(&txid);
+        }
+        let added: Vec<Txid> = txids
+            .into_iter()
+            .filter(|txid| !self.entries.contains_key(txid))
+            .collect();
+        for (txid, entry) in Self::fetch_entries(client, &added).await? {
+            self.insert(txid, entry);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            mempool_sequence = mempool.mempool_sequence,
+            txs = self.entries.len(),
+            "resynced mempool"
+        );
+        self.mempool_sequence = mempool.mempool_sequence;
+        Ok(())
+    }
+
+    /// Check the sequence number of an update.
+    /// Returns `None` if the update should be applied.
+    async fn check_sequence<C>(
+        &mut self,
+        client: &C,
+        mempool_sequence: u64,
+    ) -> Result<Option<MempoolUpdate>, Error>
+    where
+        C: ClientT + Sync,
+    {
+        if mempool_sequence < self.mempool_sequence {
+            Ok(Some(MempoolUpdate::Stale))
+        } else if mempool_sequence > self.mempool_sequence {
+            self.resync(client).await?;
+            Ok(Some(MempoolUpdate::Resynced))
+        } else {
+            self.mempool_sequence += 1;
+            Ok(None)
+        }
+    }
+
+    /// Apply an update for a transaction that was added to the mempool.
+    /// Resyncs if updates were missed.
+    pub async fn tx_added<C>(
+        &mut self,
+        client: &C,
+        txid: Txid,
+        mempool_sequence: u64,
+    ) -> Result<MempoolUpdate, Error>
+    where
+        C: ClientT + Sync,
+    {
+        if let Some(res) = self.check_sequence(client, mempool_sequence).await? {
+            return Ok(res);
+        }
+        // The transaction may have been removed already, in which case
+        // there will be a later update to remove it
+        for (txid, entry) in Self::fetch_entries(client, &[txid]).await? {
+            self.insert(txid, entry);
+        }
+        Ok(MempoolUpdate::Applied)
+    }
+
+    /// Apply an update for a transaction that was removed from the mempool.
+    /// Resyncs if updates were missed.
+    pub async fn tx_removed<C>(
+        &mut self,
+        client: &C,
+        txid: Txid,
+        mempool_sequence: u64,
+    ) -> Result<MempoolUpdate, Error>
+    where
+        C: ClientT + Sync,
+    {
+        if let Some(res) = self.check_sequence(client, mempool_sequence).await? {
+            return Ok(res);
+        }
+        self.remove(&txid);
+        Ok(MempoolUpdate::Applied)
+    }
+
+    /// Apply an event from the ZMQ `sequence` topic.
+    /// Transactions that are included in a block are removed from the
+    /// mempool without a `TxRemoved` event, so the mirror is resynced when
+    /// blocks are connected or disconnected.
+    #[cfg(feature = "zmq")]
+    pub async fn apply_sequence_event<C>(
+        &mut self,
+        client: &C,
+        event: crate::zmq::SequenceEvent,
+    ) -> Result<MempoolUpdate, Error>
+    where
+        C: ClientT + Sync,
+    {
+        use crate::zmq::SequenceEvent;
+        match event {
+            SequenceEvent::TxAdded {
+                txid,
+                mempool_sequence,
+            } => self.tx_added(client, txid, mempool_sequence).await,
+            SequenceEvent::TxRemoved {
+                txid,
+                mempool_sequence,
+            } => self.tx_removed(client, txid, mempool_sequence).await,
+            SequenceEvent::BlockConnected(_) | SequenceEvent::BlockDisconnected(_) => {
+                self.resync(client).await?;
+                Ok(MempoolUpdate::Resynced)
+            }
+        }
+    }
+}
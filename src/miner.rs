@@ -0,0 +1,162 @@
+//! CPU miner for regtest and signet.
+//!
+//! [`CpuMiner`] requests a block template, assembles a block with a
+//! [`BlockBuilder`], grinds the nonce and extranonce until the block meets
+//! the template target, and submits the block.
+//! Grinding is done on a single thread, so this is only suitable for low
+//! difficulty chains such as regtest, or signets with a challenge of
+//! `OP_TRUE`.
+
+use bitcoin::{block, Block, BlockHash, ScriptBuf, Target};
+
+use crate::{
+    block_builder::{BlockBuilder, BuildBlockError},
+    client::{
+        BlockTemplate, BlockTemplateRequest, MainClient, MainClientExt as _, SubmitBlockResult,
+    },
+    Error,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum MineError {
+    #[error(transparent)]
+    Request(#[from] Error),
+    #[error("failed to build block")]
+    BuildBlock(#[from] BuildBlockError),
+    #[error("block {block_hash} was rejected ({result:?})")]
+    Rejected {
+        block_hash: BlockHash,
+        result: SubmitBlockResult,
+    },
+}
+
+/// Grind the nonce until the header hash meets `target`.
+/// Returns `false` if the nonce space was exhausted.
+pub fn grind(header: &mut block::Header, target: Target) -> bool {
+    header.nonce = 0;
+    loop {
+        if target.is_met_by(header.block_hash()) {
+            return true;
+        }
+        match header.nonce.checked_add(1) {
+            Some(nonce) => header.nonce = nonce,
+            None => return false,
+        }
+    }
+}
+
+fn solve<F>(
+    template: &BlockTemplate,
+    payout_script_pubkey: &ScriptBuf,
+    f: F,
+) -> Result<Block, MineError>
+where
+    F: for<'a> Fn(BlockBuilder<'a>) -> BlockBuilder<'a>,
+{
+    let target = Target::from_be_bytes(template.target);
+    let mut extranonce: u64 = 0;
+    loop {
+        let builder = BlockBuilder::new(template)
+            .payout_script_pubkey(payout_script_pubkey.clone())
+            .extranonce(extranonce.to_le_bytes().to_vec());
+        let mut block = f(builder).build()?;
+        if grind(&mut block.header, target) {
+            return Ok(block);
+        }
+        extranonce += 1;
+    }
+}
+
+/// Mines blocks using block templates from the node
+#[derive(Clone, Debug)]
+pub struct CpuMiner<C> {
+    client: C,
+    payout_script_pubkey: ScriptBuf,
+}
+
+impl<C> CpuMiner<C>
+where
+    C: MainClient + Sync,
+{
+    /// The block reward is paid to `payout_script_pubkey`, after any outputs
+    /// added with [`BlockBuilder::output`]
+    pub fn new(client: C, payout_script_pubkey: ScriptBuf) -> Self {
+        Self {
+            client,
+            payout_script_pubkey,
+        }
+    }
+
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    pub async fn get_block_template(&self) -> Result<BlockTemplate, MineError> {
+        self.client
+            .get_block_template(BlockTemplateRequest::default())
+            .await
+            .map_err(|source| {
+                Error::Request {
+                    source,
+                    method: "getblocktemplate",
+                }
+                .into()
+            })
+    }
+
+    /// Assemble a block from `template` using `f` to customize the block
+    /// contents, and grind it until it meets the template target.
+    /// The extranonce is incremented each time the nonce space is exhausted.
+    /// This blocks the calling thread until the block is solved.
+    pub fn solve<F>(&self, template: &BlockTemplate, f: F) -> Result<Block, MineError>
+    where
+        F: for<'a> Fn(BlockBuilder<'a>) -> BlockBuilder<'a>,
+    {
+        solve(template, &self.payout_script_pubkey, f)
+    }
+
+    /// Mine a block using `f` to customize the block contents, eg. to add
+    /// coinbase outputs or select a subset of the template transactions.
+    /// The block is solved on the blocking thread pool.
+    pub async fn mine_block_with<F>(&self, f: F) -> Result<Block, MineError>
+    where
+        F: for<'a> Fn(BlockBuilder<'a>) -> BlockBuilder<'a> + Send + 'static,
+    {
+        let template = self.get_block_template().await?;
+        let payout_script_pubkey = self.payout_script_pubkey.clone();
+        let block = tokio::task::spawn_blocking(move || solve(&template, &payout_script_pubkey, f))
+            .await
+            .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()))?;
+        let result = self
+            .client
+            .submit_block_typed(&block)
+            .await
+            .map_err(|source| Error::Request {
+                source,
+                method: "submitblock",
+            })?;
+        if !result.is_accepted() {
+            return Err(MineError::Rejected {
+                block_hash: block.block_hash(),
+                result,
+            });
+        }
+        Ok(block)
+    }
+
+    /// Mine a block including all template transactions
+    pub async fn mine_block(&self) -> Result<Block, MineError> {
+        self.mine_block_with(|builder| builder).await
+    }
+
+    /// Mine `n_blocks` blocks including all template transactions.
+    /// Returns the hashes of the mined blocks.
+    pub async fn mine_blocks(&self, n_blocks: usize) -> Result<Vec<BlockHash>, MineError> {
+        let mut block_hashes = Vec::with_capacity(n_blocks);
+        for _ in 0..n_blocks {
+            let block = self.mine_block().await?;
+            block_hashes.push(block.block_hash());
+        }
+        Ok(block_hashes)
+    }
+}
@@ -0,0 +1,312 @@
+//! Retrying client wrapper.
+//!
+//! While bitcoind is starting up, it refuses connections, and then answers
+//! every request with `RPC_IN_WARMUP`. [`RetryClient`] retries requests with
+//! exponential backoff until the node is ready.
+
+use std::{collections::HashSet, future::Future, pin::Pin, time::Duration};
+
+use jsonrpsee::{
+    core::{
+        client::{BatchResponse, ClientT},
+        params::BatchRequestBuilder,
+        traits::ToRpcParams,
+        ClientError,
+    },
+    http_client::transport::Error as TransportError,
+};
+use serde::de::DeserializeOwned;
+
+use crate::{BitcoinRpcErrorCode, RawParams, RpcErrorExt as _};
+
+/// Methods that are not safe to retry if the request may have been processed,
+/// eg. after a timeout
+pub const NON_IDEMPOTENT_METHODS: &[&str] = &[
+    "createwallet",
+    "generate",
+    "generateblock",
+    "generatetoaddress",
+    "getnewaddress",
+    "getrawchangeaddress",
+    "importdescriptors",
+    "invalidateblock",
+    "prioritisetransaction",
+    "sendmany",
+    "sendrawtransaction",
+    "sendtoaddress",
+    "stop",
+    "submitblock",
+    "submitheader",
+    "submitpackage",
+];
+
+/// When to retry a failed request
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of retries. If `None`, retry indefinitely.
+    pub max_retries: Option<u32>,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Maximum delay between retries
+    pub max_backoff: Duration,
+    /// Factor by which the delay increases after each retry
+    pub backoff_multiplier: u32,
+    /// Methods that are only retried if the server cannot have processed the
+    /// request
+    pub non_idempotent_methods: HashSet<String>,
+}
+
+impl RetryPolicy {
+    pub fn is_idempotent(&self, method: &str) -> bool {
+        !self.non_idempotent_methods.contains(method)
+    }
+
+    /// Delay before the specified retry, starting from 0
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let multiplier = self.backoff_multiplier.saturating_pow(retry);
+        self.initial_backoff
+            .saturating_mul(multiplier)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: Some(10),
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+            backoff_multiplier: 2,
+            non_idempotent_methods: NON_IDEMPOTENT_METHODS
+                .iter()
+                .map(|method| (*method).to_owned())
+                .collect(),
+        }
+    }
+}
+
+/// Whether a failed request can be retried
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Retry {
+    /// The server did not process the request
+    Always,
+    /// The server may have processed the request
+    IfIdempotent,
+    Never,
+}
+
+impl Retry {
+    fn classify(err: &ClientError) -> Self {
+        match err {
+            ClientError::Call(_) => match err.rpc_code() {
+                Some(code) if code.is_transient() => Self::Always,
+                _ => Self::Never,
+            },
+            ClientError::Transport(err) => {
+                if let Some(TransportError::Rejected { status_code }) =
+                    err.downcast_ref::<TransportError>()
+                {
+                    // bitcoind responds with 503 if the work queue is full
+                    return if *status_code == 503 {
+                        Self::Always
+                    } else {
+                        Self::Never
+                    };
+                }
+                // Walk the source chain to find the underlying IO error
+                let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err.as_ref());
+                while let Some(err) = source {
+                    if let Some(err) = err.downcast_ref::<std::io::Error>() {
+                        return match err.kind() {
+                            std::io::ErrorKind::ConnectionRefused => Self::Always,
+                            _ => Self::IfIdempotent,
+                        };
+                    }
+                    source = err.source();
+                }
+                Self::IfIdempotent
+            }
+            ClientError::RequestTimeout => Self::IfIdempotent,
+            _ => Self::Never,
+        }
+    }
+}
+
+/// Client wrapper that retries failed requests according to a
+/// [`RetryPolicy`].
+/// Requests are retried if the node is warming up, refusing connections, or
+/// if the work queue is full.
+/// Requests for idempotent methods are also retried after timeouts and
+/// other network errors.
+/// Batches are also retried if every method in the batch is idempotent, and
+/// any request in the batch failed with a transient error such as
+/// `RPC_IN_WARMUP`.
+#[derive(Clone, Debug)]
+pub struct RetryClient<C> {
+    inner: C,
+    policy: RetryPolicy,
+}
+
+impl<C> RetryClient<C> {
+    pub fn new(inner: C, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Returns the delay before retrying, or `None` if the request should
+    /// not be retried
+    fn retry_backoff(&self, err: &ClientError, idempotent: bool, retry: u32) -> Option<Duration> {
+        let retryable = match Retry::classify(err) {
+            Retry::Always => true,
+            Retry::IfIdempotent => idempotent,
+            Retry::Never => false,
+        };
+        if !retryable {
+            return None;
+        }
+        let backoff = self.backoff(retry)?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            retry,
+            backoff = ?backoff,
+            "request failed, retrying: {err:#}"
+        );
+        Some(backoff)
+    }
+
+    /// Returns the delay before retrying a batch, or `None` if the batch
+    /// should not be retried.
+    /// bitcoind checks for warmup separately for each request in a batch, so
+    /// the batch is retried if any request failed with a transient error.
+    fn batch_retry_backoff<R>(
+        &self,
+        res: &BatchResponse<'_, R>,
+        idempotent: bool,
+        retry: u32,
+    ) -> Option<Duration>
+    where
+        R: std::fmt::Debug,
+    {
+        if !idempotent {
+            return None;
+        }
+        let err = res.iter().find_map(|entry| {
+            entry.as_ref().err().filter(|err| {
+                BitcoinRpcErrorCode::try_from(err.code())
+                    .is_ok_and(BitcoinRpcErrorCode::is_transient)
+            })
+        })?;
+        let backoff = self.backoff(retry)?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            retry,
+            backoff = ?backoff,
+            "batch request failed, retrying: {err}"
+        );
+        #[cfg(not(feature = "tracing"))]
+        let _ = err;
+        Some(backoff)
+    }
+
+    /// Returns the delay before retrying, or `None` if the maximum number of
+    /// retries has been reached
+    fn backoff(&self, retry: u32) -> Option<Duration> {
+        if self
+            .policy
+            .max_retries
+            .is_some_and(|max_retries| retry >= max_retries)
+        {
+            return None;
+        }
+        Some(self.policy.backoff(retry))
+    }
+}
+
+impl<C> ClientT for RetryClient<C>
+where
+    C: ClientT + Sync,
+{
+    async fn notification<Params>(&self, method: &str, params: Params) -> Result<(), ClientError>
+    where
+        Params: ToRpcParams + Send,
+    {
+        let params = RawParams(params.to_rpc_params()?);
+        let idempotent = self.policy.is_idempotent(method);
+        let mut retry = 0;
+        loop {
+            let err = match self.inner.notification(method, params.clone()).await {
+                Ok(()) => return Ok(()),
+                Err(err) => err,
+            };
+            let Some(backoff) = self.retry_backoff(&err, idempotent, retry) else {
+                return Err(err);
+            };
+            tokio::time::sleep(backoff).await;
+            retry += 1;
+        }
+    }
+
+    async fn request<R, Params>(&self, method: &str, params: Params) -> Result<R, ClientError>
+    where
+        R: DeserializeOwned,
+        Params: ToRpcParams + Send,
+    {
+        let params = RawParams(params.to_rpc_params()?);
+        let idempotent = self.policy.is_idempotent(method);
+        let mut retry = 0;
+        loop {
+            let err = match self.inner.request(method, params.clone()).await {
+                Ok(res) => return Ok(res),
+                Err(err) => err,
+            };
+            let Some(backoff) = self.retry_backoff(&err, idempotent, retry) else {
+                return Err(err);
+            };
+            tokio::time::sleep(backoff).await;
+            retry += 1;
+        }
+    }
+
+    async fn batch_request<'a, R>(
+        &self,
+        batch: BatchRequestBuilder<'a>,
+    ) -> Result<BatchResponse<'a, R>, ClientError>
+    where
+        R: DeserializeOwned + std::fmt::Debug + 'a,
+    {
+        let idempotent = batch
+            .iter()
+            .all(|(method, _)| self.policy.is_idempotent(method));
+        let mut retry = 0;
+        loop {
+            // Boxing the future works around a limitation when proving that
+            // the future is `Send`.
+            // See https://github.com/rust-lang/rust/issues/100013
+            let response: Pin<Box<dyn Future<Output = _> + Send + '_>> =
+                Box::pin(self.inner.batch_request(batch.clone()));
+            let backoff = match response.await {
+                Ok(res) => match self.batch_retry_backoff(&res, idempotent, retry) {
+                    Some(backoff) => backoff,
+                    None => return Ok(res),
+                },
+                Err(err) => match self.retry_backoff(&err, idempotent, retry) {
+                    Some(backoff) => backoff,
+                    None => return Err(err),
+                },
+            };
+            tokio::time::sleep(backoff).await;
+            retry += 1;
+        }
+    }
+}
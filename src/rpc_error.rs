@@ -0,0 +1,262 @@
+//! Bitcoin Core RPC error codes
+
+use jsonrpsee::{core::ClientError, types::ErrorObjectOwned};
+
+/// Error codes returned by Bitcoin Core, from `RPCErrorCode` in
+/// `src/rpc/protocol.h`
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(i32)]
+pub enum BitcoinRpcErrorCode {
+    // Standard JSON-RPC 2.0 errors
+    RpcInvalidRequest = -32600,
+    RpcMethodNotFound = -32601,
+    RpcInvalidParams = -32602,
+    RpcInternalError = -32603,
+    RpcParseError = -32700,
+
+    // General application defined errors
+    /// `std::exception` thrown in command handling
+    RpcMiscError = -1,
+    /// Unexpected type was passed as parameter
+    RpcTypeError = -3,
+    /// Invalid address or key
+    RpcInvalidAddressOrKey = -5,
+    /// Ran out of memory during operation
+    RpcOutOfMemory = -7,
+    /// Invalid, missing or duplicate parameter
+    RpcInvalidParameter = -8,
+    /// Database error
+    RpcDatabaseError = -20,
+    /// Error parsing or validating structure in raw format
+    RpcDeserializationError = -22,
+    /// General error during transaction or block submission
+    RpcVerifyError = -25,
+    /// Transaction or block was rejected by network rules
+    RpcVerifyRejected = -26,
+    /// Transaction already in chain
+    RpcVerifyAlreadyInChain = -27,
+    /// Client still warming up
+    RpcInWarmup = -28,
+    /// RPC method is deprecated
+    RpcMethodDeprecated = -32,
+
+    // P2P client errors
+    /// Bitcoin is not connected
+    RpcClientNotConnected = -9,
+    /// Still downloading initial blocks
+    RpcClientInInitialDownload = -10,
+    /// Node is already added
+    RpcClientNodeAlreadyAdded = -23,
+    /// Node has not been added before
+    RpcClientNodeNotAdded = -24,
+    /// Node to disconnect not found in connected nodes
+    RpcClientNodeNotConnected = -29,
+    /// Invalid IP/Subnet
+    RpcClientInvalidIpOrSubnet = -30,
+    /// No valid connection manager instance found
+    RpcClientP2pDisabled = -31,
+    /// No mempool instance found
+    RpcClientMempoolDisabled = -33,
+    /// Max number of outbound or block-relay connections already open
+    RpcClientNodeCapacityReached = -34,
+
+    // Wallet errors
+    /// Unspecified problem with wallet (key not found etc.)
+    RpcWalletError = -4,
+    /// Not enough funds in wallet or account
+    RpcWalletInsufficientFunds = -6,
+    /// Invalid label name
+    RpcWalletInvalidLabelName = -11,
+    /// Keypool ran out, call keypoolrefill first
+    RpcWalletKeypoolRanOut = -12,
+    /// Enter the wallet passphrase with walletpassphrase first
+    RpcWalletUnlockNeeded = -13,
+    /// The wallet passphrase entered was incorrect
+    RpcWalletPassphraseIncorrect = -14,
+    /// Command given in wrong wallet encryption state
+    RpcWalletWrongEncState = -15,
+    /// Failed to encrypt the wallet
+    RpcWalletEncryptionFailed = -16,
+    /// Wallet is already unlocked
+    RpcWalletAlreadyUnlocked = -17,
+    /// Invalid wallet specified
+    RpcWalletNotFound = -18,
+    /// No wallet specified (error when there are multiple wallets loaded)
+    RpcWalletNotSpecified = -19,
+    /// This same wallet is already loaded
+    RpcWalletAlreadyLoaded = -35,
+    /// There is already a wallet with the same name
+    RpcWalletAlreadyExists = -36,
+
+    // Unused reserved codes, kept around for backwards compatibility
+    RpcForbiddenBySafeMode = -2,
+}
+
+impl BitcoinRpcErrorCode {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// Returns `true` if the same request is expected to succeed once the
+    /// node has finished starting up
+    pub fn is_transient(self) -> bool {
+        matches!(self, Self::RpcInWarmup)
+    }
+}
+
+impl From<BitcoinRpcErrorCode> for i32 {
+    fn from(code: BitcoinRpcErrorCode) -> Self {
+        code.code()
+    }
+}
+
+impl TryFrom<i32> for BitcoinRpcErrorCode {
+    /// Unknown error code
+    type Error = i32;
+
+    fn try_from(code: i32) -> Result<Self, Self::Error> {
+        let res = match code {
+            -32600 => Self::RpcInvalidRequest,
+            -32601 => Self::RpcMethodNotFound,
+            -32602 => Self::RpcInvalidParams,
+            -32603 => Self::RpcInternalError,
+            -32700 => Self::RpcParseError,
+            -1 => Self::RpcMiscError,
+            -3 => Self::RpcTypeError,
+            -5 => Self::RpcInvalidAddressOrKey,
+            -7 => Self::RpcOutOfMemory,
+            -8 => Self::RpcInvalidParameter,
+            -20 => Self::RpcDatabaseError,
+            -22 => Self::RpcDeserializationError,
+            -25 => Self::RpcVerifyError,
+            -26 => Self::RpcVerifyRejected,
+            -27 => Self::RpcVerifyAlreadyInChain,
+            -28 => Self::RpcInWarmup,
+            -32 => Self::RpcMethodDeprecated,
+            -9 => Self::RpcClientNotConnected,
+            -10 => Self::RpcClientInInitialDownload,
+            -23 => Self::RpcClientNodeAlreadyAdded,
+            -24 => Self::RpcClientNodeNotAdded,
+            -29 => Self::RpcClientNodeNotConnected,
+            -30 => Self::RpcClientInvalidIpOrSubnet,
+            -31 => Self::RpcClientP2pDisabled,
+            -33 => Self::RpcClientMempoolDisabled,
+            -34 => Self::RpcClientNodeCapacityReached,
+            -4 => Self::RpcWalletError,
+            -6 => Self::RpcWalletInsufficientFunds,
+            -11 => Self::RpcWalletInvalidLabelName,
+            -12 => Self::RpcWalletKeypoolRanOut,
+            -13 => Self::RpcWalletUnlockNeeded,
+            -14 => Self::RpcWalletPassphraseIncorrect,
+            -15 => Self::RpcWalletWrongEncState,
+            -16 => Self::RpcWalletEncryptionFailed,
+            -17 => Self::RpcWalletAlreadyUnlocked,
+            -18 => Self::RpcWalletNotFound,
+            -19 => Self::RpcWalletNotSpecified,
+            -35 => Self::RpcWalletAlreadyLoaded,
+            -36 => Self::RpcWalletAlreadyExists,
+            -2 => Self::RpcForbiddenBySafeMode,
+            code => return Err(code),
+        };
+        Ok(res)
+    }
+}
+
+/// Reason that a transaction was rejected by `sendrawtransaction`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SendRawTransactionError {
+    /// Rejected by mempool policy or consensus rules, eg.
+    /// `min relay fee not met, 100 < 141`
+    Rejected {
+        /// Reject reason, eg. `min relay fee not met`
+        reason: String,
+        /// Additional details, eg. `100 < 141`
+        debug_message: Option<String>,
+    },
+    /// Inputs are missing or already spent
+    MissingInputs,
+    /// Transaction outputs are already in the UTXO set
+    AlreadyInChain,
+    /// Fee exceeds the `maxfeerate` argument
+    MaxFeeExceeded,
+    /// Unspendable outputs exceed the `maxburnamount` argument
+    MaxBurnExceeded,
+    /// Any other error during transaction submission
+    Other { message: String },
+}
+
+impl SendRawTransactionError {
+    /// Parse a `sendrawtransaction` error. Returns `None` if the error code
+    /// is not one used for rejected transactions.
+    pub fn from_error_object(err: &ErrorObjectOwned) -> Option<Self> {
+        let message = err.message();
+        let res = match BitcoinRpcErrorCode::try_from(err.code()).ok()? {
+            BitcoinRpcErrorCode::RpcVerifyAlreadyInChain => Self::AlreadyInChain,
+            BitcoinRpcErrorCode::RpcVerifyRejected => match message.split_once(", ") {
+                Some((reason, debug_message)) => Self::Rejected {
+                    reason: reason.to_owned(),
+                    debug_message: Some(debug_message.to_owned()),
+                },
+                None => Self::Rejected {
+                    reason: message.to_owned(),
+                    debug_message: None,
+                },
+            },
+            BitcoinRpcErrorCode::RpcVerifyError => {
+                if message == "Missing inputs" || message == "bad-txns-inputs-missingorspent" {
+                    Self::MissingInputs
+                } else if message.starts_with("Fee exceeds maximum configured by user") {
+                    Self::MaxFeeExceeded
+                } else if message
+                    .starts_with("Unspendable output exceeds maximum configured by user")
+                {
+                    Self::MaxBurnExceeded
+                } else {
+                    Self::Other {
+                        message: message.to_owned(),
+                    }
+                }
+            }
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+/// Extension trait for inspecting errors returned by Bitcoin Core
+pub trait RpcErrorExt {
+    /// The JSON-RPC error object, if the server returned an error response
+    fn rpc_error(&self) -> Option<&ErrorObjectOwned>;
+
+    /// The error code, if the server returned an error response with a known
+    /// error code
+    fn rpc_code(&self) -> Option<BitcoinRpcErrorCode> {
+        self.rpc_error()
+            .and_then(|err| BitcoinRpcErrorCode::try_from(err.code()).ok())
+    }
+
+    /// Parse an error returned by `sendrawtransaction`
+    fn send_raw_transaction_error(&self) -> Option<SendRawTransactionError> {
+        self.rpc_error()
+            .and_then(SendRawTransactionError::from_error_object)
+    }
+}
+
+impl RpcErrorExt for ClientError {
+    fn rpc_error(&self) -> Option<&ErrorObjectOwned> {
+        match self {
+            Self::Call(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl RpcErrorExt for crate::Error {
+    fn rpc_error(&self) -> Option<&ErrorObjectOwned> {
+        match self {
+            Self::Jsonrpsee { source, .. } | Self::Request { source, .. } => source.rpc_error(),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! UTXO set scans.
+//!
+//! [`TxOutSetScanner`] runs `scantxoutset`, polling the progress of the scan
+//! while it runs. A scan can be cancelled from another task with
+//! [`TxOutSetScanner::abort`].
+
+use std::{convert::Infallible, pin::pin, time::Duration};
+
+use futures::future::{self, Either};
+use jsonrpsee::core::client::ClientT;
+
+use crate::{
+    client::{
+        ScanAbort, ScanObject, ScanStart, ScanStatus, ScanTxOutSetClient as _, ScanTxOutSetResult,
+    },
+    Error,
+};
+
+/// Runs UTXO set scans.
+/// The client request timeout must be longer than the duration of a scan,
+/// which can take several minutes on mainnet.
+#[derive(Clone, Debug)]
+pub struct TxOutSetScanner<C> {
+    client: C,
+    poll_interval: Duration,
+}
+
+impl<C> TxOutSetScanner<C>
+where
+    C: ClientT + Sync,
+{
+    pub fn new(client: C, poll_interval: Duration) -> Self {
+        Self {
+            client,
+            poll_interval,
+        }
+    }
+
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Poll the scan status, calling `on_progress` with the percentage of
+    /// the UTXO set scanned. Never completes.
+    async fn poll_progress<F>(&self, mut on_progress: F) -> Infallible
+    where
+        F: FnMut(f64),
+    {
+        loop {
+            tokio::time::sleep(self.poll_interval).await;
+            match self.client.scan_tx_out_set(ScanStatus, None).await {
+                Ok(Some(status)) => on_progress(status.progress),
+                // The scan has not started yet, or has completed
+                Ok(None) => (),
+                Err(err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(%err, "failed to get scantxoutset status");
+                    #[cfg(not(feature = "tracing"))]
+                    let _ = err;
+                }
+            }
+        }
+    }
+
+    /// Scan the UTXO set for `scan_objects`, calling `on_progress` with the
+    /// percentage of the UTXO set scanned each time the status is polled.
+    /// Returns `None` if the scan was aborted.
+    /// Dropping the returned future does not stop the scan on the node, and
+    /// no other scan can be started until it completes. Call
+    /// [`Self::abort`] to stop a scan that is no longer needed.
+    pub async fn scan<F>(
+        &self,
+        scan_objects: Vec<ScanObject>,
+        on_progress: F,
+    ) -> Result<Option<ScanTxOutSetResult>, Error>
+    where
+        F: FnMut(f64),
+    {
+        let start = pin!(self.client.scan_tx_out_set(ScanStart, Some(scan_objects)));
+        let poll = pin!(self.poll_progress(on_progress));
+        let res = match future::select(start, poll).await {
+            Either::Left((res, _)) => res,
+            Either::Right((never, _)) => match never {},
+        };
+        match res {
+            Ok(res) if res.success => Ok(Some(res)),
+            Ok(_) => Ok(None),
+            Err(source) => Err(Error::Request {
+                source,
+                method: "scantxoutset",
+            }),
+        }
+    }
+
+    /// Abort the scan in progress.
+    /// Returns `false` if no scan was in progress.
+    pub async fn abort(&self) -> Result<bool, Error> {
+        self.client
+            .scan_tx_out_set(ScanAbort, None)
+            .await
+            .map_err(|source| Error::Request {
+                source,
+                method: "scantxoutset",
+            })
+    }
+}
@@ -0,0 +1,625 @@
+//! Stratum v1 pool server.
+//!
+//! [`Server`] creates a mining job for each [`BlockTemplate`], sends jobs to
+//! connected miners with `mining.notify`, validates shares submitted with
+//! `mining.submit`, and submits blocks that meet the network target with
+//! `submitblock`.
+//! Workers are not authenticated, and all shares use the same difficulty,
+//! so this is intended for small pools on test networks.
+
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+};
+
+use bitcoin::{
+    consensus::encode::VarInt,
+    hashes::{sha256d, Hash as _},
+    Block, BlockHash, ScriptBuf, Target, Transaction, TxMerkleNode, Txid,
+};
+use hashlink::LinkedHashMap;
+use serde_json::{json, Value as JsonValue};
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::broadcast,
+};
+
+use crate::{
+    block_builder::{BlockBuilder, BuildBlockError},
+    client::{BlockTemplate, MainClient, MainClientExt as _, SubmitBlockResult},
+    long_poll::BlockTemplatePoller,
+};
+
+/// Size of the extranonce assigned to each connection
+pub const EXTRANONCE1_SIZE: usize = 4;
+
+/// Maximum number of jobs that are kept for share validation
+const MAX_JOBS: usize = 64;
+
+/// Maximum number of seconds that `ntime` can be ahead of the template time
+const MAX_NTIME_OFFSET: u64 = 7200;
+
+/// Maximum length of a request line, excluding the newline.
+/// Connections that send longer lines are closed.
+const MAX_LINE_LENGTH: usize = 16 * 1024;
+
+/// JSON-RPC error code for requests that are not valid JSON
+const PARSE_ERROR_CODE: i32 = -32700;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to build block")]
+    BuildBlock(#[from] BuildBlockError),
+    #[error("failed to get block template")]
+    BlockTemplate(#[from] crate::Error),
+    #[error("io error")]
+    Io(#[from] std::io::Error),
+}
+
+/// Reasons for rejecting a share
+#[derive(Debug, thiserror::Error)]
+pub enum ShareError {
+    #[error("Job not found")]
+    JobNotFound { job_id: String },
+    #[error("Duplicate share")]
+    Duplicate,
+    #[error("Low difficulty share")]
+    LowDifficulty,
+    #[error("Unauthorized worker")]
+    Unauthorized,
+    #[error("Not subscribed")]
+    NotSubscribed,
+    #[error("Invalid extranonce2 size")]
+    InvalidExtranonce2Size,
+    #[error("ntime out of range")]
+    NTimeOutOfRange,
+    #[error("Invalid coinbase")]
+    InvalidCoinbase(#[source] bitcoin::consensus::encode::Error),
+    #[error("Failed to submit block")]
+    SubmitBlock(#[source] jsonrpsee::core::ClientError),
+}
+
+impl ShareError {
+    /// Stratum error code
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::JobNotFound { .. } => 21,
+            Self::Duplicate => 22,
+            Self::LowDifficulty => 23,
+            Self::Unauthorized => 24,
+            Self::NotSubscribed => 25,
+            Self::InvalidExtranonce2Size
+            | Self::NTimeOutOfRange
+            | Self::InvalidCoinbase(_)
+            | Self::SubmitBlock(_) => 20,
+        }
+    }
+}
+
+/// Share target for a stratum difficulty, where difficulty 1 corresponds to
+/// [`Target::MAX`]
+pub fn target_from_difficulty(difficulty: f64) -> Target {
+    // Target is `target * 2^208`
+    let target = f64::from(0xffff) / difficulty;
+    if !(difficulty > 0.0 && target < 2f64.powi(256 - 208)) {
+        return Target::from_be_bytes([0xff; 32]);
+    }
+    let bits = target.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i32;
+    if exponent == 0 {
+        return Target::ZERO;
+    }
+    let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+    let shift = exponent - 1075 + 208;
+    let mut bytes = [0; 32];
+    for bit in 0..53 {
+        let pos = bit + shift;
+        if mantissa & (1 << bit) != 0 && (0..256).contains(&pos) {
+            bytes[31 - (pos / 8) as usize] |= 1 << (pos % 8);
+        }
+    }
+    Target::from_be_bytes(bytes)
+}
+
+fn merkle_parent(left: &TxMerkleNode, right: &TxMerkleNode) -> TxMerkleNode {
+    let hash =
+        sha256d::Hash::hash(&[left.as_byte_array().as_slice(), right.as_byte_array()].concat());
+    TxMerkleNode::from_raw_hash(hash)
+}
+
+/// Merkle branch for the coinbase, given the txids of the other
+/// transactions in the block
+fn merkle_branch(txids: &[Txid]) -> Vec<TxMerkleNode> {
+    let mut level: Vec<TxMerkleNode> = txids
+        .iter()
+        .map(|txid| TxMerkleNode::from_raw_hash(txid.to_raw_hash()))
+        .collect();
+    let mut branch = Vec::new();
+    while let Some((first, rest)) = level.split_first() {
+        branch.push(*first);
+        level = rest
+            .chunks(2)
+            .map(|pair| merkle_parent(&pair[0], pair.last().unwrap()))
+            .collect();
+    }
+    branch
+}
+
+/// Previous block hash as encoded in `mining.notify`, with each 4-byte word
+/// of the internal byte order reversed
+fn encode_prev_blockhash(prev_blockhash: &BlockHash) -> String {
+    let mut bytes = prev_blockhash.to_byte_array();
+    bytes.chunks_mut(4).for_each(<[u8]>::reverse);
+    hex::encode(bytes)
+}
+
+/// Mining job for a block template
+#[derive(Debug)]
+pub struct Job {
+    id: String,
+    template: BlockTemplate,
+    /// Block with a coinbase extranonce of zeros
+    block: Block,
+    /// Serialized coinbase without witness, before the extranonce
+    coinbase1: Vec<u8>,
+    /// Serialized coinbase without witness, after the extranonce
+    coinbase2: Vec<u8>,
+    merkle_branch: Vec<TxMerkleNode>,
+    /// `true` if previous jobs are no longer valid
+    clean_jobs: bool,
+}
+
+impl Job {
+    /// The extranonce is `extranonce_size` bytes, and is split into
+    /// extranonce1 and extranonce2 by the server
+    pub fn new(
+        id: String,
+        template: BlockTemplate,
+        payout_script_pubkey: ScriptBuf,
+        extranonce_size: usize,
+        clean_jobs: bool,
+    ) -> Result<Self, BuildBlockError> {
+        let block = BlockBuilder::new(&template)
+            .payout_script_pubkey(payout_script_pubkey)
+            .extranonce(vec![0; extranonce_size])
+            .build()?;
+        let mut coinbase = block.txdata[0].clone();
+        coinbase.input[0].witness.clear();
+        let coinbase_bytes = bitcoin::consensus::serialize(&coinbase);
+        // The extranonce is the last push in the scriptSig, which follows
+        // the version, input count and null outpoint
+        let script_sig_len = coinbase.input[0].script_sig.len();
+        let script_sig_end = 4 + 1 + 36 + VarInt(script_sig_len as u64).size() + script_sig_len;
+        let coinbase1 = coinbase_bytes[..script_sig_end - extranonce_size].to_vec();
+        let coinbase2 = coinbase_bytes[script_sig_end..].to_vec();
+        let txids: Vec<Txid> = block.txdata[1..]
+            .iter()
+            .map(Transaction::compute_txid)
+            .collect();
+        Ok(Self {
+            id,
+            template,
+            merkle_branch: merkle_branch(&txids),
+            block,
+            coinbase1,
+            coinbase2,
+            clean_jobs,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn template(&self) -> &BlockTemplate {
+        &self.template
+    }
+
+    pub fn merkle_branch(&self) -> &[TxMerkleNode] {
+        &self.merkle_branch
+    }
+
+    /// Params for `mining.notify`
+    pub fn notify_params(&self, clean_jobs: bool) -> JsonValue {
+        let header = &self.block.header;
+        json!([
+            self.id,
+            encode_prev_blockhash(&header.prev_blockhash),
+            hex::encode(&self.coinbase1),
+            hex::encode(&self.coinbase2),
+            self.merkle_branch
+                .iter()
+                .map(|node| hex::encode(node.as_byte_array()))
+                .collect::<Vec<_>>(),
+            format!("{:08x}", header.version.to_consensus()),
+            format!("{:08x}", header.bits.to_consensus()),
+            format!("{:08x}", header.time),
+            clean_jobs,
+        ])
+    }
+
+    /// Assemble the block for a share
+    pub fn block(&self, extranonce: &[u8], ntime: u32, nonce: u32) -> Result<Block, ShareError> {
+        let coinbase_bytes = [&self.coinbase1, extranonce, &self.coinbase2].concat();
+        let mut coinbase: Transaction = bitcoin::consensus::deserialize(&coinbase_bytes)
+            .map_err(ShareError::InvalidCoinbase)?;
+        coinbase.input[0].witness = self.block.txdata[0].input[0].witness.clone();
+        let mut block = self.block.clone();
+        block.txdata[0] = coinbase;
+        block.header.time = ntime;
+        block.header.nonce = nonce;
+        block.header.merkle_root = block
+            .compute_merkle_root()
+            .expect("block contains a coinbase transaction");
+        Ok(block)
+    }
+}
+
+/// Accepted share
+#[derive(Clone, Debug)]
+pub struct Share {
+    pub block_hash: BlockHash,
+    /// Result of `submitblock`, if the share met the network target
+    pub submit_block_result: Option<SubmitBlockResult>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ServerConfig {
+    /// The block reward is paid to this script
+    pub payout_script_pubkey: ScriptBuf,
+    /// Size of the extranonce that is rolled by miners
+    pub extranonce2_size: usize,
+    /// Difficulty for shares
+    pub share_difficulty: f64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    jobs: LinkedHashMap<String, Arc<Job>>,
+    /// Hashes of submitted shares for the current jobs
+    shares: HashSet<BlockHash>,
+    next_job_id: u64,
+}
+
+#[derive(Debug)]
+struct Inner<C> {
+    client: C,
+    config: ServerConfig,
+    share_target: Target,
+    state: Mutex<State>,
+    jobs_tx: broadcast::Sender<Arc<Job>>,
+    next_extranonce1: AtomicU32,
+}
+
+/// Per-connection state
+#[derive(Debug, Default)]
+struct Connection {
+    extranonce1: Option<[u8; EXTRANONCE1_SIZE]>,
+    workers: HashSet<String>,
+}
+
+/// Stratum v1 server.
+/// Jobs are created from templates using [`Self::update_template`] or
+/// [`Self::follow_templates`], and miners are served with [`Self::serve`].
+#[derive(Debug)]
+pub struct Server<C>(Arc<Inner<C>>);
+
+impl<C> Clone for Server<C> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<C> Server<C>
+where
+    C: MainClient + Send + Sync + 'static,
+{
+    pub fn new(client: C, config: ServerConfig) -> Self {
+        let (jobs_tx, _) = broadcast::channel(16);
+        Self(Arc::new(Inner {
+            client,
+            share_target: target_from_difficulty(config.share_difficulty),
+            config,
+            state: Mutex::new(State::default()),
+            jobs_tx,
+            next_extranonce1: AtomicU32::new(0),
+        }))
+    }
+
+    pub fn client(&self) -> &C {
+        &self.0.client
+    }
+
+    /// Lock the shared state. The state is left consistent if a panic occurs
+    /// while it is locked, so a poisoned lock is recovered.
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.0.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The current job, if any
+    pub fn current_job(&self) -> Option<Arc<Job>> {
+        let state = self.state();
+        state.jobs.back().map(|(_, job)| job.clone())
+    }
+
+    /// Create a job for `template`, and send it to connected miners.
+    /// Previous jobs are invalidated if the template builds on a different
+    /// block.
+    pub fn update_template(&self, template: BlockTemplate) -> Result<Arc<Job>, Error> {
+        let job = {
+            let mut state = self.state();
+            let clean_jobs = state
+                .jobs
+                .back()
+                .is_none_or(|(_, job)| job.template.prev_blockhash != template.prev_blockhash);
+            let id = format!("{:x}", state.next_job_id);
+            let job = Arc::new(Job::new(
+                id.clone(),
+                template,
+                self.0.config.payout_script_pubkey.clone(),
+                EXTRANONCE1_SIZE + self.0.config.extranonce2_size,
+                clean_jobs,
+            )?);
+            state.next_job_id += 1;
+            if clean_jobs {
+                state.jobs.clear();
+                state.shares.clear();
+            }
+            state.jobs.insert(id, job.clone());
+            while state.jobs.len() > MAX_JOBS {
+                state.jobs.pop_front();
+            }
+            job
+        };
+        // Sending fails if there are no connections
+        let _ = self.0.jobs_tx.send(job.clone());
+        Ok(job)
+    }
+
+    /// Update the template each time a new template is received from
+    /// `poller`. Returns after the first error.
+    pub async fn follow_templates(&self, mut poller: BlockTemplatePoller<C>) -> Result<(), Error> {
+        loop {
+            let template = poller.next_template().await?;
+            self.update_template(template)?;
+        }
+    }
+
+    /// Validate a share, and submit the block if it meets the network target
+    pub async fn submit_share(
+        &self,
+        extranonce1: &[u8],
+        job_id: &str,
+        extranonce2: &[u8],
+        ntime: u32,
+        nonce: u32,
+    ) -> Result<Share, ShareError> {
+        let job = {
+            let state = self.state();
+            state
+                .jobs
+                .get(job_id)
+                .cloned()
+                .ok_or_else(|| ShareError::JobNotFound {
+                    job_id: job_id.to_owned(),
+                })?
+        };
+        if extranonce2.len() != self.0.config.extranonce2_size {
+            return Err(ShareError::InvalidExtranonce2Size);
+        }
+        if u64::from(ntime) < job.template.mintime
+            || u64::from(ntime) > job.template.current_time + MAX_NTIME_OFFSET
+        {
+            return Err(ShareError::NTimeOutOfRange);
+        }
+        let block = job.block(&[extranonce1, extranonce2].concat(), ntime, nonce)?;
+        let block_hash = block.block_hash();
+        if !self.0.share_target.is_met_by(block_hash) {
+            return Err(ShareError::LowDifficulty);
+        }
+        if !self.state().shares.insert(block_hash) {
+            return Err(ShareError::Duplicate);
+        }
+        let network_target = Target::from_be_bytes(job.template.target);
+        let submit_block_result = if network_target.is_met_by(block_hash) {
+            let result = match self.0.client.submit_block_typed(&block).await {
+                Ok(result) => result,
+                Err(err) => {
+                    // Allow the share to be resubmitted
+                    self.state().shares.remove(&block_hash);
+                    return Err(ShareError::SubmitBlock(err));
+                }
+            };
+            #[cfg(feature = "tracing")]
+            tracing::info!(%block_hash, ?result, "submitted block");
+            Some(result)
+        } else {
+            None
+        };
+        Ok(Share {
+            block_hash,
+            submit_block_result,
+        })
+    }
+
+    /// Accept connections from miners until an error occurs
+    pub async fn serve(&self, listener: TcpListener) -> Result<(), Error> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = self.clone();
+            tokio::spawn(async move {
+                if let Err(err) = server.handle_connection(stream).await {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(%err, "stratum connection closed");
+                    #[cfg(not(feature = "tracing"))]
+                    let _ = err;
+                }
+            });
+        }
+    }
+
+    async fn handle_request(
+        &self,
+        conn: &mut Connection,
+        method: &str,
+        params: &[JsonValue],
+        notifications: &mut Vec<JsonValue>,
+    ) -> Result<JsonValue, (i32, String)> {
+        let invalid_params = || (20, "Invalid params".to_owned());
+        match method {
+            "mining.subscribe" => {
+                let extranonce1 = self
+                    .0
+                    .next_extranonce1
+                    .fetch_add(1, Ordering::Relaxed)
+                    .to_be_bytes();
+                conn.extranonce1 = Some(extranonce1);
+                let subscription_id = hex::encode(extranonce1);
+                notifications.push(json!({
+                    "id": null,
+                    "method": "mining.set_difficulty",
+                    "params": [self.0.config.share_difficulty],
+                }));
+                if let Some(job) = self.current_job() {
+                    notifications.push(json!({
+                        "id": null,
+                        "method": "mining.notify",
+                        "params": job.notify_params(true),
+                    }));
+                }
+                Ok(json!([
+                    [
+                        ["mining.set_difficulty", subscription_id],
+                        ["mining.notify", subscription_id]
+                    ],
+                    hex::encode(extranonce1),
+                    self.0.config.extranonce2_size,
+                ]))
+            }
+            "mining.authorize" => {
+                let worker = params
+                    .first()
+                    .and_then(JsonValue::as_str)
+                    .ok_or_else(invalid_params)?;
+                conn.workers.insert(worker.to_owned());
+                Ok(json!(true))
+            }
+            "mining.submit" => {
+                let [worker, job_id, extranonce2, ntime, nonce, ..] = params else {
+                    return Err(invalid_params());
+                };
+                let worker = worker.as_str().ok_or_else(invalid_params)?;
+                let job_id = job_id.as_str().ok_or_else(invalid_params)?;
+                let extranonce2 = extranonce2
+                    .as_str()
+                    .and_then(|extranonce2| hex::decode(extranonce2).ok())
+                    .ok_or_else(invalid_params)?;
+                let parse_u32 = |value: &JsonValue| {
+                    value
+                        .as_str()
+                        .and_then(|value| u32::from_str_radix(value, 16).ok())
+                        .ok_or_else(invalid_params)
+                };
+                let (ntime, nonce) = (parse_u32(ntime)?, parse_u32(nonce)?);
+                let res = match conn.extranonce1 {
+                    None => Err(ShareError::NotSubscribed),
+                    Some(_) if !conn.workers.contains(worker) => Err(ShareError::Unauthorized),
+                    Some(extranonce1) => {
+                        self.submit_share(&extranonce1, job_id, &extranonce2, ntime, nonce)
+                            .await
+                    }
+                };
+                res.map(|_| json!(true))
+                    .map_err(|err| (err.code(), err.to_string()))
+            }
+            _ => Err((20, "Method not found".to_owned())),
+        }
+    }
+
+    async fn handle_connection(&self, stream: TcpStream) -> Result<(), std::io::Error> {
+        async fn write_message(
+            writer: &mut tokio::net::tcp::OwnedWriteHalf,
+            message: &JsonValue,
+        ) -> Result<(), std::io::Error> {
+            let mut line = message.to_string();
+            line.push('\n');
+            writer.write_all(line.as_bytes()).await
+        }
+
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        // Partially read line, which is kept if a job is sent while reading
+        let mut line = Vec::new();
+        let mut jobs_rx = self.0.jobs_tx.subscribe();
+        let mut conn = Connection::default();
+        loop {
+            // Read at most one byte past the limit, to detect long lines
+            let mut limited_reader = (&mut reader).take((MAX_LINE_LENGTH + 1 - line.len()) as u64);
+            tokio::select! {
+                n_bytes = limited_reader.read_until(b'\n', &mut line) => {
+                    let n_bytes = n_bytes?;
+                    if !line.ends_with(b"\n") {
+                        if line.len() > MAX_LINE_LENGTH {
+                            return Err(std::io::Error::new(
+                                std::io::ErrorKind::InvalidData,
+                                "line too long",
+                            ));
+                        }
+                        if n_bytes == 0 {
+                            return Ok(());
+                        }
+                        continue;
+                    }
+                    let line = std::mem::take(&mut line);
+                    let Ok(request) = serde_json::from_slice::<JsonValue>(&line) else {
+                        // The request ID is unknown
+                        let response = json!({
+                            "id": null,
+                            "result": null,
+                            "error": [PARSE_ERROR_CODE, "Parse error", null],
+                        });
+                        write_message(&mut writer, &response).await?;
+                        continue;
+                    };
+                    let method = request["method"].as_str().unwrap_or_default();
+                    let params = request["params"].as_array().map(Vec::as_slice).unwrap_or_default();
+                    let mut notifications = Vec::new();
+                    let response = match self
+                        .handle_request(&mut conn, method, params, &mut notifications)
+                        .await
+                    {
+                        Ok(result) => json!({"id": request["id"], "result": result, "error": null}),
+                        Err((code, message)) => json!({
+                            "id": request["id"],
+                            "result": null,
+                            "error": [code, message, null],
+                        }),
+                    };
+                    write_message(&mut writer, &response).await?;
+                    for notification in notifications {
+                        write_message(&mut writer, &notification).await?;
+                    }
+                }
+                job = jobs_rx.recv() => {
+                    let job = match job {
+                        Ok(job) => job,
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                    };
+                    if conn.extranonce1.is_some() {
+                        let notification = json!({
+                            "id": null,
+                            "method": "mining.notify",
+                            "params": job.notify_params(job.clean_jobs),
+                        });
+                        write_message(&mut writer, &notification).await?;
+                    }
+                }
+            }
+        }
+    }
+}
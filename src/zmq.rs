@@ -0,0 +1,343 @@
+//! Subscriber for Bitcoin Core ZMQ notifications.
+//!
+//! Endpoints can be discovered with `getzmqnotifications`, using
+//! [`Subscriber::discover`].
+//! Each message published by bitcoind includes a sequence number for the
+//! topic, which is used to detect messages that were dropped, eg. because
+//! the high water mark was reached.
+
+use std::collections::HashMap;
+
+use bitcoin::{BlockHash, Txid};
+use educe::Educe;
+use futures::Stream;
+use zeromq::{Socket as _, SocketRecv as _, SubSocket, ZmqMessage};
+
+use crate::client::{MainClient, ZMQNotification};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Topic {
+    HashBlock,
+    HashTx,
+    RawBlock,
+    RawTx,
+    Sequence,
+}
+
+impl Topic {
+    pub const ALL: [Self; 5] = [
+        Self::HashBlock,
+        Self::HashTx,
+        Self::RawBlock,
+        Self::RawTx,
+        Self::Sequence,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::HashBlock => "hashblock",
+            Self::HashTx => "hashtx",
+            Self::RawBlock => "rawblock",
+            Self::RawTx => "rawtx",
+            Self::Sequence => "sequence",
+        }
+    }
+
+    /// Notification type in `getzmqnotifications`, eg. `pubhashblock`
+    pub fn notification_type(self) -> String {
+        format!("pub{}", self.as_str())
+    }
+}
+
+impl std::fmt::Display for Topic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Topic {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|topic| topic.as_str() == s)
+            .ok_or_else(|| Error::UnknownTopic(s.to_owned()))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("zmq error")]
+    Zmq(#[from] zeromq::ZmqError),
+    #[error("failed to get zmq notifications")]
+    GetZmqNotifications(#[source] jsonrpsee::core::ClientError),
+    #[error("zmq notifications are not enabled for topic `{0}`")]
+    TopicNotEnabled(Topic),
+    #[error("topic `{topic}` is published on more than one address: {addresses:?}")]
+    TopicOnMultipleAddresses {
+        topic: Topic,
+        addresses: Vec<String>,
+    },
+    #[error("unknown topic `{0}`")]
+    UnknownTopic(String),
+    #[error("invalid message for topic `{topic}`: expected 3 frames, received {frames}")]
+    InvalidFrames { topic: String, frames: usize },
+    #[error("invalid message body for topic `{topic}`")]
+    InvalidBody { topic: Topic },
+    #[error("failed to decode message body for topic `{topic}`")]
+    Decode {
+        #[source]
+        source: bitcoin::consensus::encode::Error,
+        topic: Topic,
+    },
+}
+
+/// Event published on the `sequence` topic
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SequenceEvent {
+    BlockConnected(BlockHash),
+    BlockDisconnected(BlockHash),
+    /// Transaction added to the mempool
+    TxAdded {
+        txid: Txid,
+        mempool_sequence: u64,
+    },
+    /// Transaction removed from the mempool, for any reason other than
+    /// inclusion in a block
+    TxRemoved {
+        txid: Txid,
+        mempool_sequence: u64,
+    },
+}
+
+impl SequenceEvent {
+    /// Body is a 32-byte hash, a 1-byte label, and for mempool events, an
+    /// 8-byte little-endian mempool sequence number
+    fn decode(body: &[u8]) -> Option<Self> {
+        let (hash, rest) = body.split_first_chunk::<32>()?;
+        let (label, rest) = rest.split_first()?;
+        let mempool_sequence = || -> Option<u64> {
+            let mempool_sequence: [u8; 8] = rest.try_into().ok()?;
+            Some(u64::from_le_bytes(mempool_sequence))
+        };
+        let res = match label {
+            b'C' if rest.is_empty() => Self::BlockConnected(decode_hash(hash)),
+            b'D' if rest.is_empty() => Self::BlockDisconnected(decode_hash(hash)),
+            b'A' => Self::TxAdded {
+                txid: decode_hash(hash),
+                mempool_sequence: mempool_sequence()?,
+            },
+            b'R' => Self::TxRemoved {
+                txid: decode_hash(hash),
+                mempool_sequence: mempool_sequence()?,
+            },
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+/// Hashes are published in display order, ie. reversed
+fn decode_hash<H>(hash: &[u8; 32]) -> H
+where
+    H: bitcoin::hashes::Hash<Bytes = [u8; 32]>,
+{
+    let mut bytes = *hash;
+    bytes.reverse();
+    H::from_byte_array(bytes)
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    HashBlock(BlockHash),
+    HashTx(Txid),
+    RawBlock(bitcoin::Block),
+    RawTx(bitcoin::Transaction),
+    Sequence(SequenceEvent),
+}
+
+impl Message {
+    pub fn decode(topic: Topic, body: &[u8]) -> Result<Self, Error> {
+        let decode_err = |source| Error::Decode { source, topic };
+        let res = match topic {
+            Topic::HashBlock | Topic::HashTx => {
+                let hash: &[u8; 32] = body.try_into().map_err(|_| Error::InvalidBody { topic })?;
+                if topic == Topic::HashBlock {
+                    Self::HashBlock(decode_hash(hash))
+                } else {
+                    Self::HashTx(decode_hash(hash))
+                }
+            }
+            Topic::RawBlock => {
+                Self::RawBlock(bitcoin::consensus::deserialize(body).map_err(decode_err)?)
+            }
+            Topic::RawTx => Self::RawTx(bitcoin::consensus::deserialize(body).map_err(decode_err)?),
+            Topic::Sequence => {
+                Self::Sequence(SequenceEvent::decode(body).ok_or(Error::InvalidBody { topic })?)
+            }
+        };
+        Ok(res)
+    }
+
+    pub fn topic(&self) -> Topic {
+        match self {
+            Self::HashBlock(_) => Topic::HashBlock,
+            Self::HashTx(_) => Topic::HashTx,
+            Self::RawBlock(_) => Topic::RawBlock,
+            Self::RawTx(_) => Topic::RawTx,
+            Self::Sequence(_) => Topic::Sequence,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub message: Message,
+    /// Sequence number for the topic
+    pub sequence: u32,
+    /// Number of messages on the topic that were missed since the previous
+    /// message on the topic, detected from the sequence numbers.
+    /// If non-zero, the subscriber should resync, eg. using
+    /// [`crate::ChainFollower`] or `getrawmempool`.
+    pub missed: u32,
+}
+
+/// Subscriber for ZMQ notifications
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct Subscriber {
+    #[educe(Debug(ignore))]
+    socket: SubSocket,
+    /// Last sequence number received for each topic
+    sequences: HashMap<Topic, u32>,
+}
+
+impl Subscriber {
+    /// Connect to `endpoints` and subscribe to `topics`.
+    /// Each topic must be published on only one of the endpoints, since
+    /// each publisher has its own sequence numbers, and missed messages are
+    /// detected from the sequence numbers for each topic.
+    pub async fn connect<'a, I>(endpoints: I, topics: &[Topic]) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut socket = SubSocket::new();
+        for endpoint in endpoints {
+            socket.connect(endpoint).await?;
+        }
+        for topic in topics {
+            socket.subscribe(topic.as_str()).await?;
+        }
+        Ok(Self {
+            socket,
+            sequences: HashMap::new(),
+        })
+    }
+
+    /// Connect to the endpoints in `notifications`, as returned by
+    /// `getzmqnotifications`, and subscribe to `topics`.
+    /// Returns an error if a topic is not enabled, or is published on more
+    /// than one address.
+    pub async fn from_notifications(
+        notifications: &[ZMQNotification],
+        topics: &[Topic],
+    ) -> Result<Self, Error> {
+        let mut endpoints = Vec::<&str>::new();
+        for topic in topics {
+            let notification_type = topic.notification_type();
+            let mut addresses = Vec::<&str>::new();
+            for notification in notifications
+                .iter()
+                .filter(|notification| notification.notification_type == notification_type)
+            {
+                if !addresses.contains(&notification.address.as_str()) {
+                    addresses.push(&notification.address);
+                }
+            }
+            let address = match addresses.as_slice() {
+                [] => return Err(Error::TopicNotEnabled(*topic)),
+                [address] => *address,
+                _ => {
+                    return Err(Error::TopicOnMultipleAddresses {
+                        topic: *topic,
+                        addresses: addresses.into_iter().map(str::to_owned).collect(),
+                    })
+                }
+            };
+            if !endpoints.contains(&address) {
+                endpoints.push(address);
+            }
+        }
+        Self::connect(endpoints, topics).await
+    }
+
+    /// Discover endpoints using `getzmqnotifications`, and subscribe to
+    /// `topics`.
+    /// Returns an error if a topic is not enabled, or is published on more
+    /// than one address.
+    pub async fn discover<C>(client: &C, topics: &[Topic]) -> Result<Self, Error>
+    where
+        C: MainClient + Sync,
+    {
+        let notifications = client
+            .get_zmq_notifications()
+            .await
+            .map_err(Error::GetZmqNotifications)?;
+        Self::from_notifications(&notifications, topics).await
+    }
+
+    fn decode(&mut self, message: ZmqMessage) -> Result<Notification, Error> {
+        let frames = message.into_vec();
+        let [topic, body, sequence] = frames.as_slice() else {
+            return Err(Error::InvalidFrames {
+                topic: frames
+                    .first()
+                    .map(|topic| String::from_utf8_lossy(topic).into_owned())
+                    .unwrap_or_default(),
+                frames: frames.len(),
+            });
+        };
+        let topic: Topic = std::str::from_utf8(topic)
+            .map_err(|_| Error::UnknownTopic(String::from_utf8_lossy(topic).into_owned()))?
+            .parse()?;
+        let sequence: [u8; 4] = sequence
+            .as_ref()
+            .try_into()
+            .map_err(|_| Error::InvalidBody { topic })?;
+        let sequence = u32::from_le_bytes(sequence);
+        let missed = match self.sequences.insert(topic, sequence) {
+            Some(prev) => sequence.wrapping_sub(prev).wrapping_sub(1),
+            None => 0,
+        };
+        #[cfg(feature = "tracing")]
+        if missed != 0 {
+            tracing::warn!(%topic, sequence, missed, "missed zmq messages");
+        }
+        let message = Message::decode(topic, body)?;
+        Ok(Notification {
+            message,
+            sequence,
+            missed,
+        })
+    }
+
+    /// Receive the next notification
+    pub async fn recv(&mut self) -> Result<Notification, Error> {
+        let message = self.socket.recv().await?;
+        self.decode(message)
+    }
+
+    /// Stream of notifications.
+    /// Errors decoding individual messages do not end the stream.
+    /// The stream ends after the first socket error.
+    pub fn into_stream(self) -> impl Stream<Item = Result<Notification, Error>> {
+        futures::stream::unfold(Some(self), |subscriber| async move {
+            let mut subscriber = subscriber?;
+            match subscriber.recv().await {
+                Err(err @ Error::Zmq(_)) => Some((Err(err), None)),
+                res => Some((res, Some(subscriber))),
+            }
+        })
+    }
+}